@@ -0,0 +1,129 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// The answer to one part of a puzzle, as a list of named values.
+///
+/// The first value is the one Advent of Code actually asks for. Any others are supporting details
+/// which show some of the working, eg the gamma and epsilon rates that are multiplied to get the power.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Answer {
+    fields: Vec<(String, Value)>,
+}
+
+impl Answer {
+    /// An answer whose main value is `value`, called `name`
+    pub fn new(name: &str, value: impl Into<Value>) -> Answer {
+        Answer {
+            fields: vec![(name.to_string(), value.into())],
+        }
+    }
+
+    /// Add a supporting value after the main one
+    pub fn with(mut self, name: &str, value: impl Into<Value>) -> Answer {
+        self.fields.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// The main value, ie the one that would be submitted to Advent of Code
+    pub fn value(&self) -> &Value {
+        &self.fields[0].1
+    }
+
+    /// Every value, main one first
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+impl std::fmt::Display for Answer {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        let fields = self
+            .fields()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .join(", ");
+        write!(fmt, "{}", fields)
+    }
+}
+
+/// A single value within an `Answer`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(untagged)]
+pub enum Value {
+    Number(i64),
+    Text(String),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            Value::Number(n) => write!(fmt, "{}", n),
+            Value::Text(s) => write!(fmt, "{}", s),
+        }
+    }
+}
+
+macro_rules! value_from_small_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Value {
+            fn from(n: $t) -> Value {
+                Value::Number(i64::from(n))
+            }
+        })*
+    };
+}
+
+value_from_small_int!(u8, u16, u32, i8, i16, i32, i64);
+
+macro_rules! value_from_large_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Value {
+            /// Numbers too big for an i64 are kept as text, so that they're at least displayed correctly
+            fn from(n: $t) -> Value {
+                i64::try_from(n)
+                    .map(Value::Number)
+                    .unwrap_or_else(|_| Value::Text(n.to_string()))
+            }
+        })*
+    };
+}
+
+value_from_large_int!(u64, usize);
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::Text(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn answer_works() {
+        let answer = Answer::new("power", 198u64)
+            .with("gamma", 22u16)
+            .with("epsilon", 9u16);
+        assert_eq!(&Value::Number(198), answer.value());
+        assert_eq!(
+            vec!["power", "gamma", "epsilon"],
+            answer.fields().map(|(name, _)| name).collect::<Vec<_>>()
+        );
+        assert_eq!("power=198, gamma=22, epsilon=9", answer.to_string());
+    }
+
+    #[test]
+    pub fn huge_numbers_become_text() {
+        assert_eq!(Value::Number(-3), Value::from(-3i32));
+        assert_eq!(Value::Number(1 << 40), Value::from(1usize << 40));
+        assert_eq!(Value::Text(u64::MAX.to_string()), Value::from(u64::MAX));
+    }
+}
@@ -1,5 +1,32 @@
+use crate::error::{LineError, PuzzleError};
+use std::fmt::Display;
+
+/// Offsets to the four cells sharing an edge with a cell, clockwise from the one above
+pub const ORTHOGONAL: &[(i64, i64)] = &[(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets to all eight cells surrounding a cell, including diagonals, clockwise from the one above
+pub const SURROUNDING: &[(i64, i64)] = &[
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// What happens to neighbours which would fall off the edge of the grid
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Edges {
+    /// They're skipped, so cells on the edge have fewer neighbours
+    Bounded,
+    /// They wrap around to the opposite edge, as if the grid were a torus
+    Wrapping,
+}
+
 /// A rectangular grid of objects, randomly addressable.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct ArrayGrid<T>
 where
     T: Clone + Default,
@@ -26,12 +53,92 @@ where
         ArrayGrid::create(size, size)
     }
 
+    /// `arr` holds the cells row by row, so it must contain exactly `width * height` of them
     pub fn create_from(width: usize, height: usize, arr: Vec<T>) -> ArrayGrid<T> {
-        debug_assert_eq!(arr.len(), width * height);
+        assert_eq!(
+            arr.len(),
+            width * height,
+            "A {}x{} grid needs {} cells",
+            width,
+            height,
+            width * height
+        );
 
         ArrayGrid { width, height, arr }
     }
 
+    /// Parse a rectangular block of text with one cell per character, eg a map like
+    /// ```text
+    /// #..#
+    /// .##.
+    /// ```
+    /// Every row must be the same width, and there must be at least one. Trailing newlines are ignored.
+    pub fn parse(
+        input: &str,
+        mut parse_cell: impl FnMut(char) -> T,
+    ) -> Result<ArrayGrid<T>, GridError> {
+        ArrayGrid::try_parse(input, |c| Ok::<T, &str>(parse_cell(c)))
+    }
+
+    /// Like `parse()`, except that `parse_cell` can reject a character, with the reason why
+    pub fn try_parse<E: Display>(
+        input: &str,
+        mut parse_cell: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<ArrayGrid<T>, GridError> {
+        let input = input.trim_end_matches(&['\r', '\n'][..]);
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err(GridError::Empty);
+        }
+
+        let mut arr: Vec<T> = Vec::with_capacity(input.len());
+        let mut height = 0;
+        for (line_idx, line) in input.lines().enumerate() {
+            let line_width = line.chars().count();
+            if line_width != width {
+                let reason = format!(
+                    "expected every row to be {} wide, but this one is {}",
+                    width, line_width
+                );
+                return Err(GridError::Line {
+                    line_idx,
+                    error: LineError::new(line, line, reason),
+                });
+            }
+
+            for (idx, c) in line.char_indices() {
+                let cell = parse_cell(c).map_err(|reason| GridError::Line {
+                    line_idx,
+                    error: LineError::new(line, &line[idx..idx + c.len_utf8()], reason),
+                })?;
+                arr.push(cell);
+            }
+            height += 1;
+        }
+
+        Ok(ArrayGrid { width, height, arr })
+    }
+
+    /// Parse a block of single digits, like most of the puzzle inputs that are grids
+    pub fn parse_digits(input: &str) -> Result<ArrayGrid<T>, GridError>
+    where
+        T: From<u8>,
+    {
+        ArrayGrid::try_parse(input, |c| {
+            c.to_digit(10)
+                .map(|d| T::from(d as u8))
+                .ok_or("expected a single digit")
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
     pub fn set(&mut self, xu: usize, yu: usize, val: T) {
         let idx = self.get_index(xu, yu);
         self.arr[idx] = val;
@@ -55,6 +162,11 @@ where
         &mut self.arr[idx]
     }
 
+    /// The cells row by row as one slice, eg to split them up between threads
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.arr
+    }
+
     fn get_index(&self, xu: usize, yu: usize) -> usize {
         xu + yu * self.width
     }
@@ -63,24 +175,30 @@ where
         self.arr.iter()
     }
 
-    pub fn rows(&self) -> impl Iterator<Item = Box<dyn Iterator<Item = &T> + '_>> + '_ {
-        self.arr.chunks(self.width).map(|chunks| {
-            let row: Box<dyn Iterator<Item = &T>> = Box::new(chunks.iter());
-            row
-        })
+    /// Every cell, row by row, for changing in place
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.arr.iter_mut()
     }
 
-    pub fn columns(&self) -> impl Iterator<Item = Box<dyn Iterator<Item = &T> + '_>> + '_ {
-        let mut index: usize = 0;
-        std::iter::from_fn(move || {
-            if index < self.width {
-                let col: Box<dyn Iterator<Item = &T>> = Box::new(self.get_column(index));
-                index += 1;
-                Some(col)
-            } else {
-                None
-            }
-        })
+    pub fn rows(&self) -> impl Iterator<Item = std::slice::Iter<'_, T>> + '_ {
+        self.arr.chunks(self.width.max(1)).map(|row| row.iter())
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = std::iter::StepBy<std::slice::Iter<'_, T>>> + '_ {
+        (0..self.width).map(move |col_idx| self.arr[col_idx..].iter().step_by(self.width))
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = std::slice::IterMut<'_, T>> + '_ {
+        self.arr
+            .chunks_mut(self.width.max(1))
+            .map(|row| row.iter_mut())
+    }
+
+    pub fn columns_mut(&mut self) -> ColumnsMut<'_, T> {
+        ColumnsMut {
+            remaining: self.width,
+            rows: self.rows_mut().collect(),
+        }
     }
 
     pub fn get_row(&self, row_idx: usize) -> impl Iterator<Item = &T> {
@@ -92,6 +210,175 @@ where
     pub fn get_column(&self, col_idx: usize) -> impl Iterator<Item = &T> {
         self.arr[col_idx..].iter().step_by(self.width)
     }
+
+    /// A new grid of the same size, with each cell converted by `f`
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> ArrayGrid<U>
+    where
+        U: Clone + Default,
+    {
+        ArrayGrid::create_from(self.width, self.height, self.arr.iter().map(f).collect())
+    }
+
+    /// A new grid combining each cell with the one in the same place in `other`, which must be the same size
+    pub fn zip_with<U, V>(
+        &self,
+        other: &ArrayGrid<U>,
+        mut f: impl FnMut(&T, &U) -> V,
+    ) -> ArrayGrid<V>
+    where
+        U: Clone + Default,
+        V: Clone + Default,
+    {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "Can't zip a {}x{} grid with a {}x{} one",
+            self.width,
+            self.height,
+            other.width,
+            other.height
+        );
+        let arr = self
+            .arr
+            .iter()
+            .zip(other.arr.iter())
+            .map(|(a, b)| f(a, b))
+            .collect();
+        ArrayGrid::create_from(self.width, self.height, arr)
+    }
+
+    /// Set every cell to `val`
+    pub fn fill(&mut self, val: T) {
+        self.arr.fill(val);
+    }
+
+    /// The number of cells matching `predicate`
+    pub fn count_where(&self, mut predicate: impl FnMut(&T) -> bool) -> usize {
+        self.arr.iter().filter(|&c| predicate(c)).count()
+    }
+
+    /// The cells above, below, left and right of (x, y), stopping at the edges of the grid
+    pub fn orthogonal_neighbors(&self, x: usize, y: usize) -> Neighbors<'_, T> {
+        self.neighbors(x, y, ORTHOGONAL, Edges::Bounded)
+    }
+
+    /// All eight cells surrounding (x, y), including diagonals, stopping at the edges of the grid
+    pub fn all_neighbors(&self, x: usize, y: usize) -> Neighbors<'_, T> {
+        self.neighbors(x, y, SURROUNDING, Edges::Bounded)
+    }
+
+    /// The cells at each of the `offsets` from (x, y), eg `ORTHOGONAL`, or a custom stencil like `&[(2, 0), (-2, 0)]`.
+    /// Each is yielded with its coordinates, in the same order as the offsets.
+    pub fn neighbors<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'a [(i64, i64)],
+        edges: Edges,
+    ) -> Neighbors<'a, T> {
+        Neighbors {
+            grid: self,
+            x: x as i64,
+            y: y as i64,
+            offsets: offsets.iter(),
+            edges,
+        }
+    }
+}
+
+/// Why a block of text couldn't be parsed into an `ArrayGrid`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum GridError {
+    /// There wasn't any text, or the first row was blank
+    Empty,
+    /// A row was the wrong width, or one of its cells couldn't be parsed.
+    /// `line_idx` is zero-based, as produced by `enumerate()`.
+    Line { line_idx: usize, error: LineError },
+}
+
+impl GridError {
+    pub fn into_puzzle_error(self, puzzle: &'static str) -> PuzzleError {
+        match self {
+            GridError::Empty => PuzzleError::unsolvable(puzzle, "the grid was empty"),
+            GridError::Line { line_idx, error } => PuzzleError::at_line(puzzle, line_idx, error),
+        }
+    }
+}
+
+impl std::fmt::Display for GridError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            GridError::Empty => write!(fmt, "the grid was empty"),
+            GridError::Line { line_idx, error } => write!(
+                fmt,
+                "bad grid at line {}, column {}: {} (found \"{}\")",
+                line_idx + 1,
+                error.column,
+                error.reason,
+                error.text
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// Iterator over the columns of a grid, see `ArrayGrid::columns_mut()`.
+/// The cells in a column aren't next to each other, so it steps through every row at once, taking the next
+/// cell from each of them for each column.
+pub struct ColumnsMut<'a, T> {
+    remaining: usize,
+    rows: Vec<std::slice::IterMut<'a, T>>,
+}
+
+impl<'a, T> Iterator for ColumnsMut<'a, T> {
+    type Item = std::vec::IntoIter<&'a mut T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let column: Vec<&'a mut T> = self.rows.iter_mut().filter_map(|row| row.next()).collect();
+        Some(column.into_iter())
+    }
+}
+
+/// Iterator over the neighbours of a cell, see `ArrayGrid::neighbors()`
+pub struct Neighbors<'a, T>
+where
+    T: Clone + Default,
+{
+    grid: &'a ArrayGrid<T>,
+    x: i64,
+    y: i64,
+    offsets: std::slice::Iter<'a, (i64, i64)>,
+    edges: Edges,
+}
+
+impl<'a, T> Iterator for Neighbors<'a, T>
+where
+    T: Clone + Default,
+{
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let width = self.grid.width as i64;
+        let height = self.grid.height as i64;
+
+        for &(dx, dy) in self.offsets.by_ref() {
+            let (x, y) = match self.edges {
+                Edges::Bounded => (self.x + dx, self.y + dy),
+                Edges::Wrapping => (
+                    (self.x + dx).rem_euclid(width),
+                    (self.y + dy).rem_euclid(height),
+                ),
+            };
+            if let Some(val) = self.grid.get_checked(x, y) {
+                return Some(((x as usize, y as usize), val));
+            }
+        }
+        None
+    }
 }
 
 #[cfg(test)]
@@ -123,4 +410,174 @@ mod test {
         assert_eq!(8, *rows[2][1]);
         assert_eq!(9, *rows[2][2]);
     }
+
+    #[test]
+    pub fn columns_iterator_works() {
+        let grid = numbered_grid();
+        let columns: Vec<Vec<u16>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(vec![vec![0, 3, 6], vec![1, 4, 7], vec![2, 5, 8]], columns);
+    }
+
+    #[test]
+    pub fn mutable_iterators_work() {
+        let mut grid = numbered_grid();
+        for cell in grid.iter_mut() {
+            *cell *= 10;
+        }
+        for (y, row) in grid.rows_mut().enumerate() {
+            for cell in row {
+                *cell += y as u16;
+            }
+        }
+        for (x, column) in grid.columns_mut().enumerate() {
+            for cell in column {
+                *cell += 100 * x as u16;
+            }
+        }
+        let expected: Vec<u16> = vec![0, 110, 220, 31, 141, 251, 62, 172, 282];
+        assert_eq!(expected, grid.iter().copied().collect::<Vec<_>>());
+
+        let mut wide: ArrayGrid<u8> = ArrayGrid::create_from(3, 2, (0..6).collect());
+        let mut columns = wide.columns_mut();
+        let middle = columns.nth(1).unwrap();
+        for cell in middle {
+            *cell = 9;
+        }
+        assert_eq!(1, columns.count());
+        assert_eq!(
+            vec![0, 9, 2, 3, 9, 5],
+            wide.iter().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    pub fn bulk_operations_work() {
+        let mut grid = numbered_grid();
+        let evens = grid.map(|&n| n % 2 == 0);
+        assert_eq!(5, evens.count_where(|&even| even));
+        assert_eq!(2, grid.count_where(|&n| n > 6));
+
+        let sums = grid.zip_with(&evens, |&n, &even| if even { n } else { 0 });
+        assert_eq!(
+            vec![0, 0, 2, 0, 4, 0, 6, 0, 8],
+            sums.iter().copied().collect::<Vec<_>>()
+        );
+
+        grid.fill(7);
+        assert_eq!(9, grid.count_where(|&n| n == 7));
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn zip_with_checks_size() {
+        let grid = numbered_grid();
+        let other: ArrayGrid<u16> = ArrayGrid::create(2, 3);
+        grid.zip_with(&other, |a, b| a + b);
+    }
+
+    /// 0 1 2
+    /// 3 4 5
+    /// 6 7 8
+    fn numbered_grid() -> ArrayGrid<u16> {
+        ArrayGrid::create_from(3, 3, (0..9).collect())
+    }
+
+    #[test]
+    pub fn parse_works() {
+        let grid = ArrayGrid::parse("#..\n.#.\n", |c| c == '#').unwrap();
+        assert_eq!(3, grid.width());
+        assert_eq!(2, grid.height());
+        assert!(*grid.get(0, 0));
+        assert!(!*grid.get(2, 0));
+        assert!(*grid.get(1, 1));
+
+        let grid: ArrayGrid<u8> = ArrayGrid::parse_digits("123\r\n456").unwrap();
+        assert_eq!(&6, grid.get(2, 1));
+    }
+
+    #[test]
+    pub fn parse_errors() {
+        assert_eq!(
+            Err(GridError::Empty),
+            ArrayGrid::parse("\n\n", |c| c).map(|_| ())
+        );
+        assert_eq!(
+            Err(GridError::Empty),
+            ArrayGrid::<u8>::parse_digits("").map(|_| ())
+        );
+
+        match ArrayGrid::<u8>::parse_digits("123\n45\n678") {
+            Err(GridError::Line { line_idx, error }) => {
+                assert_eq!(1, line_idx);
+                assert_eq!("45", error.text);
+                assert!(error.reason.contains("3 wide"));
+            }
+            other => panic!("Expected a ragged row error but got {:?}", other),
+        }
+
+        let err = ArrayGrid::<u8>::parse_digits("123\n4é6").unwrap_err();
+        assert_eq!(
+            "bad grid at line 2, column 2: expected a single digit (found \"é\")",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn create_from_checks_size() {
+        ArrayGrid::create_from(3, 3, vec![0u8; 8]);
+    }
+
+    fn values<'a>(neighbors: impl Iterator<Item = ((usize, usize), &'a u16)>) -> Vec<u16> {
+        neighbors.map(|(_, &val)| val).collect()
+    }
+
+    #[test]
+    pub fn orthogonal_neighbors_works() {
+        let grid = numbered_grid();
+        assert_eq!(vec![1, 5, 7, 3], values(grid.orthogonal_neighbors(1, 1)));
+        assert_eq!(vec![1, 3], values(grid.orthogonal_neighbors(0, 0)));
+        assert_eq!(vec![5, 7], values(grid.orthogonal_neighbors(2, 2)));
+
+        let coords: Vec<(usize, usize)> = grid.orthogonal_neighbors(2, 0).map(|(c, _)| c).collect();
+        assert_eq!(vec![(2, 1), (1, 0)], coords);
+    }
+
+    #[test]
+    pub fn all_neighbors_works() {
+        let grid = numbered_grid();
+        assert_eq!(
+            vec![1, 2, 5, 8, 7, 6, 3, 0],
+            values(grid.all_neighbors(1, 1))
+        );
+        assert_eq!(vec![1, 4, 3], values(grid.all_neighbors(0, 0)));
+    }
+
+    #[test]
+    pub fn wrapping_neighbors_works() {
+        let grid = numbered_grid();
+        assert_eq!(
+            vec![6, 1, 3, 2],
+            values(grid.neighbors(0, 0, ORTHOGONAL, Edges::Wrapping))
+        );
+        let offsets = &[(1, 1), (1, 0), (0, 0), (0, 1)];
+        assert_eq!(
+            vec![0, 6, 8, 2],
+            values(grid.neighbors(2, 2, offsets, Edges::Wrapping))
+        );
+    }
+
+    #[test]
+    pub fn stencil_neighbors_works() {
+        let grid = numbered_grid();
+        let knight = &[(1, 2), (2, 1), (-1, 2), (-2, 1)];
+        assert_eq!(
+            vec![7, 5],
+            values(grid.neighbors(0, 0, knight, Edges::Bounded))
+        );
+        assert_eq!(
+            Vec::<u16>::new(),
+            values(grid.neighbors(1, 1, knight, Edges::Bounded))
+        );
+    }
 }
@@ -0,0 +1,237 @@
+use crate::array_grid::{ArrayGrid, Edges, Neighbors, ORTHOGONAL, SURROUNDING};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// What the rule for a generation step can see when working out the next value of one cell:
+/// the cell itself, where it is, and the rest of the previous generation around it
+pub struct Neighborhood<'a, T>
+where
+    T: Clone + Default,
+{
+    grid: &'a ArrayGrid<T>,
+    x: usize,
+    y: usize,
+}
+
+impl<'a, T> Neighborhood<'a, T>
+where
+    T: Clone + Default,
+{
+    /// The cell's value in the previous generation
+    pub fn value(&self) -> &'a T {
+        self.grid.get(self.x, self.y)
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// The previous generation's cell at the given offset from this one, or None if that's off the edge
+    pub fn relative(&self, dx: i64, dy: i64) -> Option<&'a T> {
+        self.grid
+            .get_checked(self.x as i64 + dx, self.y as i64 + dy)
+    }
+
+    pub fn orthogonal_neighbors(&self) -> Neighbors<'a, T> {
+        self.grid
+            .neighbors(self.x, self.y, ORTHOGONAL, Edges::Bounded)
+    }
+
+    pub fn all_neighbors(&self) -> Neighbors<'a, T> {
+        self.grid
+            .neighbors(self.x, self.y, SURROUNDING, Edges::Bounded)
+    }
+
+    /// See `ArrayGrid::neighbors()`
+    pub fn neighbors(&self, offsets: &'a [(i64, i64)], edges: Edges) -> Neighbors<'a, T> {
+        self.grid.neighbors(self.x, self.y, offsets, edges)
+    }
+}
+
+impl<T> ArrayGrid<T>
+where
+    T: Clone + Default + PartialEq,
+{
+    /// Work out the next generation of a cellular automaton, where `rule` gives each cell's new value based on
+    /// the previous generation. Every cell sees the old grid, so the order they're updated in doesn't matter.
+    pub fn step(&self, rule: impl Fn(&Neighborhood<T>) -> T) -> ArrayGrid<T> {
+        let mut next = self.clone();
+        self.step_into(&mut next, &rule);
+        next
+    }
+
+    /// Keep stepping until a step doesn't change anything, returning the final grid and the number of steps
+    /// that did change something. `max_steps` is a safety net for rules which never settle down.
+    pub fn step_until_stable(
+        &self,
+        max_steps: usize,
+        rule: impl Fn(&Neighborhood<T>) -> T,
+    ) -> (ArrayGrid<T>, usize) {
+        self.iterate_until_stable(max_steps, |current, next| current.step_into(next, &rule))
+    }
+
+    /// Write the generation after this one into `next`, which must be the same size.
+    /// Returns whether any cell changed.
+    fn step_into(&self, next: &mut ArrayGrid<T>, rule: &impl Fn(&Neighborhood<T>) -> T) -> bool {
+        let mut changed = false;
+        for (y, row) in next.rows_mut().enumerate() {
+            changed |= self.update_row(y, row, rule);
+        }
+        changed
+    }
+
+    /// Alternate between two buffers rather than allocating a new grid for every generation
+    fn iterate_until_stable(
+        &self,
+        max_steps: usize,
+        mut step: impl FnMut(&ArrayGrid<T>, &mut ArrayGrid<T>) -> bool,
+    ) -> (ArrayGrid<T>, usize) {
+        let mut current = self.clone();
+        let mut next = self.clone();
+        for steps in 0..max_steps {
+            if !step(&current, &mut next) {
+                return (current, steps);
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        (current, max_steps)
+    }
+
+    fn update_row<'a>(
+        &self,
+        y: usize,
+        row: impl Iterator<Item = &'a mut T>,
+        rule: &impl Fn(&Neighborhood<T>) -> T,
+    ) -> bool
+    where
+        T: 'a,
+    {
+        let mut changed = false;
+        for (x, cell) in row.enumerate() {
+            let val = rule(&Neighborhood { grid: self, x, y });
+            changed |= val != *self.get(x, y);
+            *cell = val;
+        }
+        changed
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<T> ArrayGrid<T>
+where
+    T: Clone + Default + PartialEq + Send + Sync,
+{
+    /// Like `step()`, but with the rows shared out between threads
+    pub fn par_step(&self, rule: impl Fn(&Neighborhood<T>) -> T + Sync) -> ArrayGrid<T> {
+        let mut next = self.clone();
+        self.par_step_into(&mut next, &rule);
+        next
+    }
+
+    /// Like `step_until_stable()`, but with the rows of each step shared out between threads
+    pub fn par_step_until_stable(
+        &self,
+        max_steps: usize,
+        rule: impl Fn(&Neighborhood<T>) -> T + Sync,
+    ) -> (ArrayGrid<T>, usize) {
+        self.iterate_until_stable(max_steps, |current, next| {
+            current.par_step_into(next, &rule)
+        })
+    }
+
+    fn par_step_into(
+        &self,
+        next: &mut ArrayGrid<T>,
+        rule: &(impl Fn(&Neighborhood<T>) -> T + Sync),
+    ) -> bool {
+        let width = self.width().max(1);
+        next.as_mut_slice()
+            .par_chunks_mut(width)
+            .enumerate()
+            .map(|(y, row)| self.update_row(y, row.iter_mut(), rule))
+            .reduce(|| false, |a, b| a || b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Conway's Game of Life
+    fn life(cell: &Neighborhood<bool>) -> bool {
+        let alive = cell.all_neighbors().filter(|(_, &alive)| alive).count();
+        matches!((cell.value(), alive), (true, 2) | (_, 3))
+    }
+
+    fn draw(grid: &ArrayGrid<bool>) -> String {
+        grid.render_with(|&alive| if alive { '#' } else { '.' })
+            .to_string()
+    }
+
+    #[test]
+    pub fn step_works() {
+        let blinker = ArrayGrid::parse(".....\n..#..\n..#..\n..#..\n.....", |c| c == '#').unwrap();
+        let next = blinker.step(life);
+        assert_eq!(".....\n.....\n.###.\n.....\n.....", draw(&next));
+        assert_eq!(draw(&blinker), draw(&next.step(life)));
+    }
+
+    #[test]
+    pub fn step_until_stable_works() {
+        //The highest value spreads one cell further each step
+        let grid = ArrayGrid::create_from(5, 1, vec![9u8, 0, 0, 0, 0]);
+        let spread = |cell: &Neighborhood<u8>| {
+            let highest = cell.orthogonal_neighbors().map(|(_, &v)| v).max();
+            highest.unwrap_or(0).max(*cell.value())
+        };
+        let (settled, steps) = grid.step_until_stable(100, spread);
+        assert_eq!(4, steps);
+        assert_eq!(5, settled.count_where(|&v| v == 9));
+
+        let (_, steps) = grid.step_until_stable(2, spread);
+        assert_eq!(2, steps);
+
+        //A blinker never settles down
+        let blinker = ArrayGrid::parse("...\n###\n...", |c| c == '#').unwrap();
+        let (_, steps) = blinker.step_until_stable(10, life);
+        assert_eq!(10, steps);
+    }
+
+    #[test]
+    pub fn relative_works() {
+        let grid: ArrayGrid<u8> = ArrayGrid::create_from(3, 1, vec![1, 2, 3]);
+        let shifted = grid.step(|cell| *cell.relative(-1, 0).unwrap_or(&0));
+        assert_eq!(vec![0, 1, 2], shifted.iter().copied().collect::<Vec<_>>());
+        assert_eq!(
+            vec![0, 1, 2],
+            grid.step(|cell| cell.position().0 as u8)
+                .iter()
+                .copied()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    pub fn parallel_matches_sequential() {
+        let glider = ArrayGrid::parse(
+            ".#......\n..#.....\n###.....\n........\n........\n........",
+            |c| c == '#',
+        )
+        .unwrap();
+        let mut sequential = glider.clone();
+        let mut parallel = glider;
+        for _ in 0..8 {
+            sequential = sequential.step(life);
+            parallel = parallel.par_step(life);
+            assert_eq!(draw(&sequential), draw(&parallel));
+        }
+
+        let grid = ArrayGrid::create_from(5, 1, vec![9u8, 0, 0, 0, 0]);
+        let (_, steps) = grid.par_step_until_stable(100, |cell| {
+            let highest = cell.orthogonal_neighbors().map(|(_, &v)| v).max();
+            highest.unwrap_or(0).max(*cell.value())
+        });
+        assert_eq!(4, steps);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::error::{PuzzleError, PuzzleResult};
+use crate::report::{format_duration, nanos, print_table};
+use crate::solver::{Part, Puzzle};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// How much slower than the baseline a phase can get before it's flagged, when `--threshold` isn't given
+pub const DEFAULT_THRESHOLD_PERCENT: f64 = 10.0;
+
+/// Timing statistics for one phase of a puzzle, eg parsing, or solving part 1
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub std_dev: Duration,
+}
+
+impl Stats {
+    /// Returns None if there aren't any samples
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 1 {
+            sorted[mid]
+        } else {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        };
+
+        let secs: Vec<f64> = samples.iter().map(|d| d.as_secs_f64()).collect();
+        let mean = secs.iter().sum::<f64>() / secs.len() as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / secs.len() as f64;
+
+        Some(Stats {
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+/// The timings of every phase of one puzzle, over many iterations
+#[derive(Debug)]
+pub struct Benchmark {
+    pub puzzle: &'static str,
+    /// Keyed by phase, ie "parse", "part1" or "part2", in the order they ran
+    pub phases: Vec<(String, Stats)>,
+}
+
+/// Parse and solve the input `iterations` times, timing each phase separately.
+/// Parts which aren't implemented yet are left out, but any other error stops the benchmark.
+pub fn bench(
+    puzzle: &'static dyn Puzzle,
+    input: &str,
+    parts: &[Part],
+    iterations: usize,
+) -> PuzzleResult<Benchmark> {
+    let mut parse_samples = Vec::with_capacity(iterations);
+    let mut part_samples: Vec<(Part, Vec<Duration>)> = vec![];
+
+    for _ in 0..iterations {
+        let run = puzzle.run(input, parts)?;
+        parse_samples.push(run.parse_time);
+
+        for part_run in run.parts {
+            match part_run.answer {
+                Ok(_) => {}
+                Err(PuzzleError::NotImplemented { .. }) => continue,
+                Err(e) => return Err(e),
+            }
+            match part_samples.iter_mut().find(|(p, _)| *p == part_run.part) {
+                Some((_, samples)) => samples.push(part_run.solve_time),
+                None => part_samples.push((part_run.part, vec![part_run.solve_time])),
+            }
+        }
+    }
+
+    let phases = std::iter::once((String::from("parse"), parse_samples))
+        .chain(
+            part_samples
+                .into_iter()
+                .map(|(part, samples)| (format!("part{}", part), samples)),
+        )
+        .filter_map(|(phase, samples)| Stats::from_samples(&samples).map(|stats| (phase, stats)))
+        .collect();
+
+    Ok(Benchmark {
+        puzzle: puzzle.name(),
+        phases,
+    })
+}
+
+/// Median times from an earlier benchmark, to compare against. Stored as TOML, eg
+/// ```toml
+/// [sonar]
+/// parse = 831120
+/// part1 = 645540
+/// ```
+/// Times are in nanoseconds.
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(transparent)]
+pub struct Baseline {
+    puzzles: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> PuzzleResult<Baseline> {
+        let to_error = |reason: String| PuzzleError::Input {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let raw = std::fs::read_to_string(path).map_err(|e| to_error(e.to_string()))?;
+        toml::from_str(&raw).map_err(|e| to_error(e.to_string()))
+    }
+
+    /// Write the baseline to `path`, replacing whatever was there
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let raw = toml::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, raw).map_err(|e| format!("couldn't write {}: {}", path.display(), e))
+    }
+
+    /// Replace the times for the benchmarked puzzle with its latest medians.
+    /// Any other puzzles are left alone.
+    pub fn record(&mut self, benchmark: &Benchmark) {
+        let times = benchmark
+            .phases
+            .iter()
+            .map(|(phase, stats)| (phase.clone(), nanos(stats.median)))
+            .collect();
+        self.puzzles.insert(benchmark.puzzle.to_string(), times);
+    }
+
+    pub fn get(&self, puzzle: &str, phase: &str) -> Option<Duration> {
+        let nanos = self.puzzles.get(puzzle)?.get(phase)?;
+        Some(Duration::from_nanos(*nanos))
+    }
+}
+
+/// How a phase's median compares with the baseline
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Change {
+    pub baseline: Duration,
+    /// Positive when slower than the baseline, eg 12.5 means it took 12.5% longer
+    pub percent: f64,
+    pub regressed: bool,
+}
+
+impl Change {
+    pub fn new(baseline: Duration, median: Duration, threshold_percent: f64) -> Change {
+        let percent = if baseline.is_zero() {
+            0.0
+        } else {
+            (median.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0
+        };
+        Change {
+            baseline,
+            percent,
+            regressed: percent > threshold_percent,
+        }
+    }
+}
+
+/// Print a row for each phase of each benchmark, compared with the baseline if there is one.
+/// Returns true if nothing regressed.
+pub fn print_benchmarks(
+    benchmarks: &[Benchmark],
+    baseline: Option<&Baseline>,
+    threshold_percent: f64,
+) -> bool {
+    let mut header: Vec<String> = ["Puzzle", "Phase", "Min", "Median", "Mean", "Std dev"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    if baseline.is_some() {
+        header.push(String::from("Baseline"));
+        header.push(String::from("Change"));
+    }
+
+    let mut regressions = 0;
+    let mut rows = vec![];
+    for benchmark in benchmarks {
+        for (phase, stats) in &benchmark.phases {
+            let mut row = vec![
+                benchmark.puzzle.to_string(),
+                phase.clone(),
+                format_duration(stats.min),
+                format_duration(stats.median),
+                format_duration(stats.mean),
+                format_duration(stats.std_dev),
+            ];
+            if let Some(baseline) = baseline {
+                match baseline.get(benchmark.puzzle, phase) {
+                    Some(time) => {
+                        let change = Change::new(time, stats.median, threshold_percent);
+                        row.push(format_duration(change.baseline));
+                        if change.regressed {
+                            regressions += 1;
+                            row.push(format!("{:+.1}% REGRESSED", change.percent));
+                        } else {
+                            row.push(format!("{:+.1}%", change.percent));
+                        }
+                    }
+                    None => {
+                        row.push(String::from("-"));
+                        row.push(String::from("-"));
+                    }
+                }
+            }
+            rows.push(row);
+        }
+    }
+
+    print_table(&header, &rows, None);
+
+    if regressions > 0 {
+        eprintln!(
+            "{} phase(s) were more than {}% slower than the baseline",
+            regressions, threshold_percent
+        );
+    }
+    regressions == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::answer::Answer;
+    use crate::solver::{find_by_name, Solver};
+
+    /// A puzzle where only part 1 has been solved so far
+    struct Unfinished;
+
+    impl Solver for Unfinished {
+        type Input = ();
+
+        fn name(&self) -> &'static str {
+            "unfinished"
+        }
+
+        fn day(&self) -> usize {
+            0
+        }
+
+        fn input_file(&self) -> &'static str {
+            "unfinished.txt"
+        }
+
+        fn parse(&self, _input: &str) -> PuzzleResult<()> {
+            Ok(())
+        }
+
+        fn part1(&self, _input: &()) -> PuzzleResult<Answer> {
+            Ok(Answer::new("answer", 1u32))
+        }
+
+        fn part2(&self, _input: &()) -> PuzzleResult<Answer> {
+            Err(PuzzleError::NotImplemented {
+                puzzle: "unfinished",
+                part: Part::Two,
+            })
+        }
+    }
+
+    #[test]
+    pub fn stats_work() {
+        let ms = Duration::from_millis;
+        assert_eq!(None, Stats::from_samples(&[]));
+
+        let stats = Stats::from_samples(&[ms(4), ms(2), ms(6), ms(4)]).unwrap();
+        assert_eq!(ms(2), stats.min);
+        assert_eq!(ms(4), stats.median);
+        assert_eq!(ms(4), stats.mean);
+        //Population std dev of 2, 4, 4, 6 is sqrt(2)
+        assert_eq!(1414, stats.std_dev.as_micros());
+
+        let stats = Stats::from_samples(&[ms(1), ms(3)]).unwrap();
+        assert_eq!(ms(2), stats.median);
+    }
+
+    #[test]
+    pub fn bench_skips_unimplemented_parts() {
+        let benchmark = bench(&Unfinished, "", &[Part::One, Part::Two], 3).unwrap();
+        let phases: Vec<&str> = benchmark.phases.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(vec!["parse", "part1"], phases);
+
+        bench(find_by_name("whales").unwrap(), "1,x", &[Part::One], 3)
+            .expect_err("Parse errors should stop the benchmark");
+    }
+
+    #[test]
+    pub fn baseline_works() {
+        let stats = Stats::from_samples(&[Duration::from_micros(110)]).unwrap();
+        let benchmark = Benchmark {
+            puzzle: "sonar",
+            phases: vec![(String::from("part1"), stats)],
+        };
+
+        let mut baseline: Baseline =
+            toml::from_str("[sonar]\npart1 = 100000\n[dive]\nparse = 5").unwrap();
+        let change = Change::new(baseline.get("sonar", "part1").unwrap(), stats.median, 5.0);
+        assert!(change.regressed);
+        assert!((change.percent - 10.0).abs() < 0.001);
+        assert!(!Change::new(Duration::from_micros(100), stats.median, 15.0).regressed);
+
+        baseline.record(&benchmark);
+        assert_eq!(
+            Some(Duration::from_micros(110)),
+            baseline.get("sonar", "part1")
+        );
+        assert_eq!(Some(Duration::from_nanos(5)), baseline.get("dive", "parse"));
+        assert_eq!(None, baseline.get("sonar", "parse"));
+    }
+}
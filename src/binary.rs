@@ -2,31 +2,63 @@
 //It was fun mucking around with bits, but I suspect this is a wildly verbose and inefficient solution
 //It probably would've been a whole lot easier to just work with the characters separately
 
+use crate::answer::Answer;
+use crate::error::{parse_lines, LineError, PuzzleError, PuzzleResult};
+use crate::solver::Solver;
+
+const NAME: &str = "binary";
+
 const BIT_LENGTH: usize = 12; //all the inputs are actually 12 bits long
 const NUM_MASK: u16 = 0x0FFF; //mask a u16 down to 12 bits
 
-pub fn run(input: String) -> Result<String, String> {
-    let binary_lines: Vec<u16> = input
-        .lines()
-        .map(parse_binary)
-        .map(|r| r.expect("Failed to parse binary value"))
-        .collect();
+pub struct Binary;
+
+impl Solver for Binary {
+    type Input = Vec<u16>;
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
 
-    let most_common_bits = find_most_common_bits(binary_lines.iter());
-    println!("Most common bits: {:?}", most_common_bits);
+    fn day(&self) -> usize {
+        3
+    }
+
+    fn input_file(&self) -> &'static str {
+        "binary.txt"
+    }
+
+    fn parse(&self, input: &str) -> PuzzleResult<Vec<u16>> {
+        let report: Vec<u16> =
+            parse_lines(NAME, input, parse_binary).collect::<PuzzleResult<_>>()?;
+        if report.is_empty() {
+            return Err(PuzzleError::unsolvable(NAME, "the report was empty"));
+        }
+        Ok(report)
+    }
 
-    let gamma = bits_to_int(&most_common_bits);
-    let epsilon = !gamma & NUM_MASK;
-    let power = gamma as u64 * epsilon as u64;
+    fn part1(&self, binary_lines: &Vec<u16>) -> PuzzleResult<Answer> {
+        let most_common_bits = find_most_common_bits(binary_lines.iter());
+        log::debug!("Most common bits: {:?}", most_common_bits);
 
-    let oxy_power = find_lr_value(&binary_lines, true);
-    let co2_rating = find_lr_value(&binary_lines, false);
-    let lr_rating = oxy_power as u64 * co2_rating as u64;
+        let gamma = bits_to_int(&most_common_bits);
+        let epsilon = !gamma & NUM_MASK;
+        let power = gamma as u64 * epsilon as u64;
 
-    Ok(format!(
-        "Gamma: {}, Epsilon: {}, Power: {}\nOxy: {}, CO2: {}, Life Support: {}",
-        gamma, epsilon, power, oxy_power, co2_rating, lr_rating
-    ))
+        Ok(Answer::new("power", power)
+            .with("gamma", gamma)
+            .with("epsilon", epsilon))
+    }
+
+    fn part2(&self, binary_lines: &Vec<u16>) -> PuzzleResult<Answer> {
+        let oxy_power = find_lr_value(binary_lines, true);
+        let co2_rating = find_lr_value(binary_lines, false);
+        let lr_rating = oxy_power as u64 * co2_rating as u64;
+
+        Ok(Answer::new("life_support", lr_rating)
+            .with("oxy", oxy_power)
+            .with("co2", co2_rating))
+    }
 }
 
 fn find_lr_value(nums: &[u16], find_most_common: bool) -> u16 {
@@ -111,9 +143,24 @@ fn extract_bit(num: &u16, pos: u16) -> u16 {
     (num >> pos) & 0x1
 }
 
-fn parse_binary(s: &str) -> Result<u16, String> {
-    let b: u16 = u16::from_str_radix(s, 2).expect("Failed to parse string to u16 as binary");
-    Ok(b)
+fn parse_binary(s: &str) -> Result<u16, LineError> {
+    if let Some((idx, c)) = s.char_indices().find(|(_, c)| *c != '0' && *c != '1') {
+        return Err(LineError::new(
+            s,
+            &s[idx..idx + c.len_utf8()],
+            "expected only binary digits",
+        ));
+    }
+    if s.is_empty() || s.len() > BIT_LENGTH {
+        return Err(LineError::new(
+            s,
+            s,
+            format!("expected 1 to {} binary digits", BIT_LENGTH),
+        ));
+    }
+
+    //Can't fail now that we've checked the digits & length
+    Ok(u16::from_str_radix(s, 2).unwrap())
 }
 
 #[cfg(test)]
@@ -170,6 +217,16 @@ mod test {
         assert_eq!(Ok(131u16), parse_binary("000010000011"));
     }
 
+    #[test]
+    pub fn parse_binary_errors() {
+        let err = parse_binary("000020000011").unwrap_err();
+        assert_eq!(5, err.column);
+        assert_eq!("2", err.text);
+
+        parse_binary("").expect_err("Empty lines are not binary numbers");
+        parse_binary("0000100000110").expect_err("Too many bits");
+    }
+
     #[test]
     pub fn extract_bit_works() {
         let input = parse_binary("10100011").unwrap();
@@ -1,31 +1,67 @@
-pub fn run(input: String) -> Result<String, String> {
-    let (pos, multiple) = run_internal(input)?;
-    Ok(format!(
-        "Range: {}, Depth: {}, Aim: {}, multiple: {}",
-        pos.range, pos.depth, pos.aim, multiple
-    ))
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, LineError, PuzzleError, PuzzleResult};
+use crate::solver::Solver;
+
+const NAME: &str = "dive";
+
+pub struct Dive;
+
+impl Solver for Dive {
+    type Input = Vec<Command>;
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn day(&self) -> usize {
+        2
+    }
+
+    fn input_file(&self) -> &'static str {
+        "dive.txt"
+    }
+
+    fn parse(&self, input: &str) -> PuzzleResult<Vec<Command>> {
+        parse_lines(NAME, input, parse_command).collect()
+    }
+
+    fn part1(&self, commands: &Vec<Command>) -> PuzzleResult<Answer> {
+        //Without aim, up & down move the sub directly. Which is exactly how the aim changes, so just reuse that.
+        let (pos, _) = run_internal(commands)?;
+        let multiple = pos.range * pos.aim;
+        Ok(Answer::new("multiple", multiple)
+            .with("range", pos.range)
+            .with("depth", pos.aim))
+    }
+
+    fn part2(&self, commands: &Vec<Command>) -> PuzzleResult<Answer> {
+        let (pos, multiple) = run_internal(commands)?;
+        Ok(Answer::new("multiple", multiple)
+            .with("range", pos.range)
+            .with("depth", pos.depth)
+            .with("aim", pos.aim))
+    }
 }
 
-#[allow(unused_parens)]
-fn run_internal(input: String) -> Result<(SubState, u64), String> {
+fn run_internal(commands: &[Command]) -> PuzzleResult<(SubState, u64)> {
     let mut pos = SubState {
         aim: 0,
         range: 0,
         depth: 0,
     };
-    let commands_results = input.lines().map(parse_command);
-
-    for result in commands_results {
-        match result {
-            Err(e) => return Err(e),
-            Ok(command) => match command {
-                Command::Forward { distance: d } => {
-                    pos.range += d;
-                    pos.depth += (d * pos.aim);
-                }
-                Command::Down { distance: d } => pos.aim += d,
-                Command::Up { distance: d } => pos.aim -= d,
-            },
+
+    for command in commands {
+        match *command {
+            Command::Forward { distance: d } => {
+                pos.range += d;
+                pos.depth += d * pos.aim;
+            }
+            Command::Down { distance: d } => pos.aim += d,
+            Command::Up { distance: d } => {
+                pos.aim = pos.aim.checked_sub(d).ok_or_else(|| {
+                    PuzzleError::unsolvable(NAME, "the sub would have to rise above the surface")
+                })?
+            }
         }
     }
 
@@ -33,28 +69,28 @@ fn run_internal(input: String) -> Result<(SubState, u64), String> {
     Ok((pos, multiple))
 }
 
-fn parse_command(line: &str) -> Result<Command, String> {
-    let parts: Vec<&str> = line.trim().split_whitespace().collect();
+fn parse_command(line: &str) -> Result<Command, LineError> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
     if let [direction, distance, ..] = parts.as_slice() {
-        match distance.parse::<u64>() {
-            Err(e) => Err(format!(
-                "Unable to parse distance from \"{}\": {}",
-                distance,
-                e.to_string()
+        let distance = parse_number::<u64>(line, distance)?;
+        match direction {
+            direction if "forward".eq_ignore_ascii_case(direction) => {
+                Ok(Command::Forward { distance })
+            }
+            direction if "up".eq_ignore_ascii_case(direction) => Ok(Command::Up { distance }),
+            direction if "down".eq_ignore_ascii_case(direction) => Ok(Command::Down { distance }),
+            _ => Err(LineError::new(
+                line,
+                direction,
+                "expected one of forward, up or down",
             )),
-            Ok(distance) => match direction {
-                direction if "forward".eq_ignore_ascii_case(direction) => {
-                    Ok(Command::Forward { distance })
-                }
-                direction if "up".eq_ignore_ascii_case(direction) => Ok(Command::Up { distance }),
-                direction if "down".eq_ignore_ascii_case(direction) => {
-                    Ok(Command::Down { distance })
-                }
-                _ => Err(format!("Unable to understand direction \"{}\"", direction)),
-            },
         }
     } else {
-        Err(format!("Unable to parse command \"{}\"", line))
+        Err(LineError::new(
+            line,
+            line,
+            "expected a direction and a distance",
+        ))
     }
 }
 
@@ -65,7 +101,7 @@ struct SubState {
 }
 
 #[derive(PartialEq, Debug, Eq)]
-enum Command {
+pub enum Command {
     Forward { distance: u64 },
     Up { distance: u64 },
     Down { distance: u64 },
@@ -76,7 +112,7 @@ mod test {
     use super::*;
 
     #[test]
-    pub fn parse_command_ok() -> Result<(), String> {
+    pub fn parse_command_ok() -> Result<(), LineError> {
         assert_eq!(parse_command("down 2")?, Command::Down { distance: 2 });
         assert_eq!(parse_command("up 77")?, Command::Up { distance: 77 });
         assert_eq!(
@@ -94,12 +130,39 @@ mod test {
         parse_command("up up and away").expect_err("This is not OK!");
     }
 
+    #[test]
+    pub fn parse_command_err_location() {
+        let err = parse_command("forward 1O").unwrap_err();
+        assert_eq!(9, err.column);
+        assert_eq!("1O", err.text);
+
+        let err = parse_command("sideways 10").unwrap_err();
+        assert_eq!(1, err.column);
+        assert_eq!("sideways", err.text);
+    }
+
     #[test]
     pub fn run_works() {
         let input = "down 20\nforward 20\nup 20\nforward 10";
-        let result = run_internal(String::from(input)).expect("Should not have failed");
+        let commands = Dive.parse(input).expect("Should have parsed");
+        let result = run_internal(&commands).expect("Should not have failed");
         assert_eq!(30, result.0.range);
         assert_eq!(400, result.0.depth);
         assert_eq!(12000, result.1);
     }
+
+    #[test]
+    pub fn sample_works() {
+        let input = Dive
+            .parse("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2")
+            .unwrap();
+        assert_eq!(
+            "multiple=150, range=15, depth=10",
+            Dive.part1(&input).unwrap().to_string()
+        );
+        assert_eq!(
+            "multiple=900, range=15, depth=60, aim=10",
+            Dive.part2(&input).unwrap().to_string()
+        );
+    }
 }
@@ -0,0 +1,199 @@
+use crate::solver::Part;
+use std::fmt::Display;
+use std::str::FromStr;
+
+pub type PuzzleResult<T> = Result<T, PuzzleError>;
+
+/// Everything that can go wrong while solving a puzzle
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum PuzzleError {
+    /// The input file couldn't be read at all
+    Input {
+        path: String,
+        reason: String,
+    },
+    /// Part of the puzzle input was malformed.
+    /// Line and column numbers start at 1, to match what an editor would show.
+    Parse {
+        puzzle: &'static str,
+        line: usize,
+        column: usize,
+        text: String,
+        reason: String,
+    },
+    /// The input parsed fine, but there was no answer to be found in it
+    Unsolvable {
+        puzzle: &'static str,
+        reason: String,
+    },
+    NotImplemented {
+        puzzle: &'static str,
+        part: Part,
+    },
+}
+
+impl PuzzleError {
+    /// Attach the location of a line to an error from parsing within it
+    /// `line_idx` is the zero-based index of the line, as produced by `enumerate()`
+    pub fn at_line(puzzle: &'static str, line_idx: usize, err: LineError) -> PuzzleError {
+        PuzzleError::Parse {
+            puzzle,
+            line: line_idx + 1,
+            column: err.column,
+            text: err.text,
+            reason: err.reason,
+        }
+    }
+
+    pub fn unsolvable(puzzle: &'static str, reason: impl Display) -> PuzzleError {
+        PuzzleError::Unsolvable {
+            puzzle,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for PuzzleError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            PuzzleError::Input { path, reason } => {
+                write!(fmt, "couldn't read {}: {}", path, reason)
+            }
+            PuzzleError::Parse {
+                puzzle,
+                line,
+                column,
+                text,
+                reason,
+            } => write!(
+                fmt,
+                "{}: bad input at line {}, column {}: {} (found \"{}\")",
+                puzzle, line, column, reason, text
+            ),
+            PuzzleError::Unsolvable { puzzle, reason } => {
+                write!(fmt, "{}: no solution found: {}", puzzle, reason)
+            }
+            PuzzleError::NotImplemented { puzzle, part } => {
+                write!(fmt, "{}: part {} is not implemented", puzzle, part)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+/// An error within a single line of input, which doesn't yet know which line it came from.
+/// Turn it into a `PuzzleError` using `PuzzleError::at_line()`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct LineError {
+    pub column: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl LineError {
+    /// `text` should be a slice of `line`, so that the column can be worked out from where it sits.
+    /// If it isn't, the error is reported at column 1.
+    pub fn new(line: &str, text: &str, reason: impl Display) -> LineError {
+        let offset = offset_within(line, text).unwrap_or(0);
+
+        LineError {
+            column: line[..offset].chars().count() + 1,
+            text: text.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Where `inner` starts within `outer`, in bytes, or None if it isn't a slice of `outer`
+pub(crate) fn offset_within(outer: &str, inner: &str) -> Option<usize> {
+    (inner.as_ptr() as usize)
+        .checked_sub(outer.as_ptr() as usize)
+        .filter(|&offset| offset + inner.len() <= outer.len())
+}
+
+/// Parse every line of the input with `parser`, attaching line numbers to any errors
+pub fn parse_lines<'a, T, F>(
+    puzzle: &'static str,
+    input: &'a str,
+    parser: F,
+) -> impl Iterator<Item = PuzzleResult<T>> + 'a
+where
+    F: Fn(&'a str) -> Result<T, LineError> + 'a,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(move |(idx, line)| parser(line).map_err(|e| PuzzleError::at_line(puzzle, idx, e)))
+}
+
+/// Parse `token`, which must be a slice of `line`, as a number
+pub fn parse_number<T>(line: &str, token: &str) -> Result<T, LineError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    token
+        .parse::<T>()
+        .map_err(|e| LineError::new(line, token, format!("not a valid number: {}", e)))
+}
+
+/// Parse a line of numbers separated by `separator`, eg "1,2,3"
+pub fn parse_separated<T>(line: &str, separator: char) -> Result<Vec<T>, LineError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    line.split(separator)
+        .map(|token| parse_number(line, token.trim()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn line_error_finds_column() {
+        let line = "12,x4,56";
+        let err = LineError::new(line, &line[3..5], "bad");
+        assert_eq!(4, err.column);
+        assert_eq!("x4", err.text);
+
+        let err = LineError::new(line, "not from the line", "bad");
+        assert_eq!(1, err.column);
+    }
+
+    #[test]
+    pub fn offset_within_checks_bounds() {
+        let line = "12,x4,56";
+        assert_eq!(Some(3), offset_within(line, &line[3..5]));
+        assert_eq!(Some(8), offset_within(line, &line[8..]));
+        assert_eq!(None, offset_within(&line[..4], &line[3..5]));
+        assert_eq!(None, offset_within(&line[3..], &line[..5]));
+    }
+
+    #[test]
+    pub fn parse_separated_works() {
+        assert_eq!(Ok(vec![1u8, 2, 3]), parse_separated("1,2, 3", ','));
+
+        let err = parse_separated::<u8>("1,2,three,4", ',').unwrap_err();
+        assert_eq!(5, err.column);
+        assert_eq!("three", err.text);
+    }
+
+    #[test]
+    pub fn parse_lines_reports_line_numbers() {
+        let results: Vec<PuzzleResult<u16>> =
+            parse_lines("test", "1\n2\nnope", |line| parse_number(line, line)).collect();
+        assert_eq!(Ok(1), results[0]);
+        assert_eq!(Ok(2), results[1]);
+        match &results[2] {
+            Err(PuzzleError::Parse { line, column, .. }) => {
+                assert_eq!(3, *line);
+                assert_eq!(1, *column);
+            }
+            other => panic!("Expected a parse error but got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use crate::array_grid::ArrayGrid;
+use std::collections::HashMap;
+
+/// Storage for a 2D grid of cells, so that a solver can switch between `ArrayGrid`, `SparseGrid` and
+/// `GrowingGrid` without being rewritten.
+///
+/// Coordinates are signed, since the sparse and growing grids can extend into negative coordinates.
+pub trait Grid<T>
+where
+    T: Clone + Default,
+{
+    /// The cell at (x, y), or None if it's outside the grid (or for a `SparseGrid`, has never been written)
+    fn cell(&self, x: i64, y: i64) -> Option<&T>;
+
+    /// The cell at (x, y), growing the grid to include it if it's outside.
+    /// Panics for an `ArrayGrid`, which can't grow.
+    fn cell_mut(&mut self, x: i64, y: i64) -> &mut T;
+
+    fn set_cell(&mut self, x: i64, y: i64, val: T) {
+        *self.cell_mut(x, y) = val;
+    }
+
+    /// The smallest rectangle containing every cell that's been written, which for a fixed size `ArrayGrid`
+    /// is the whole grid. None if there aren't any cells.
+    fn bounds(&self) -> Option<Bounds>;
+
+    /// Every cell in the grid with its coordinates. For a `SparseGrid` that's only the ones which were
+    /// written, in no particular order; the others go row by row.
+    fn cells(&self) -> Box<dyn Iterator<Item = ((i64, i64), &T)> + '_>;
+
+    /// Copy the cells within the bounds into an `ArrayGrid`, eg to render it.
+    /// The top left corner of the bounds ends up at (0, 0).
+    fn to_array_grid(&self) -> ArrayGrid<T> {
+        let bounds = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return ArrayGrid::create(0, 0),
+        };
+
+        let mut grid = ArrayGrid::create(bounds.width(), bounds.height());
+        for ((x, y), val) in self.cells() {
+            grid.set(
+                (x - bounds.min_x) as usize,
+                (y - bounds.min_y) as usize,
+                val.clone(),
+            );
+        }
+        grid
+    }
+}
+
+/// An inclusive rectangle of coordinates
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Bounds {
+    pub min_x: i64,
+    pub min_y: i64,
+    pub max_x: i64,
+    pub max_y: i64,
+}
+
+impl Bounds {
+    /// Bounds containing just the one point
+    pub fn point(x: i64, y: i64) -> Bounds {
+        Bounds {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max_x - self.min_x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max_y - self.min_y + 1) as usize
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
+
+    /// The smallest bounds containing both these ones and (x, y)
+    pub fn including(&self, x: i64, y: i64) -> Bounds {
+        Bounds {
+            min_x: self.min_x.min(x),
+            min_y: self.min_y.min(y),
+            max_x: self.max_x.max(x),
+            max_y: self.max_y.max(y),
+        }
+    }
+
+    /// Every point within the bounds, row by row
+    pub fn points(&self) -> impl Iterator<Item = (i64, i64)> {
+        let Bounds {
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+        } = *self;
+        (min_y..=max_y).flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+    }
+}
+
+impl<T> Grid<T> for ArrayGrid<T>
+where
+    T: Clone + Default,
+{
+    fn cell(&self, x: i64, y: i64) -> Option<&T> {
+        self.get_checked(x, y)
+    }
+
+    fn cell_mut(&mut self, x: i64, y: i64) -> &mut T {
+        assert!(
+            x >= 0 && y >= 0 && (x as usize) < self.width() && (y as usize) < self.height(),
+            "({}, {}) is outside of the {}x{} ArrayGrid, which can't grow",
+            x,
+            y,
+            self.width(),
+            self.height()
+        );
+        self.get_mut(x as usize, y as usize)
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        if self.width() == 0 || self.height() == 0 {
+            None
+        } else {
+            Some(Bounds {
+                min_x: 0,
+                min_y: 0,
+                max_x: self.width() as i64 - 1,
+                max_y: self.height() as i64 - 1,
+            })
+        }
+    }
+
+    fn cells(&self) -> Box<dyn Iterator<Item = ((i64, i64), &T)> + '_> {
+        let width = self.width().max(1);
+        Box::new(
+            self.iter()
+                .enumerate()
+                .map(move |(idx, val)| (((idx % width) as i64, (idx / width) as i64), val)),
+        )
+    }
+}
+
+/// A grid which only stores the cells that have been written, for when they're few and far between
+#[derive(Clone, Debug, Default)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(i64, i64), T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    /// The number of cells which have been written
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl<T> Grid<T> for SparseGrid<T>
+where
+    T: Clone + Default,
+{
+    fn cell(&self, x: i64, y: i64) -> Option<&T> {
+        self.cells.get(&(x, y))
+    }
+
+    fn cell_mut(&mut self, x: i64, y: i64) -> &mut T {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.including(x, y),
+            None => Bounds::point(x, y),
+        });
+        self.cells.entry((x, y)).or_default()
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    fn cells(&self) -> Box<dyn Iterator<Item = ((i64, i64), &T)> + '_> {
+        Box::new(self.cells.iter().map(|(&point, val)| (point, val)))
+    }
+}
+
+/// A dense grid which grows in any direction, including into negative coordinates, to fit whatever is written.
+/// It grows by at least double in each direction that it needs to, so that writing in a line doesn't
+/// reallocate every time.
+#[derive(Clone, Debug, Default)]
+pub struct GrowingGrid<T> {
+    /// The area that has space allocated for it, which is often bigger than `written`
+    allocated: Option<Bounds>,
+    written: Option<Bounds>,
+    arr: Vec<T>,
+}
+
+impl<T> GrowingGrid<T>
+where
+    T: Clone + Default,
+{
+    pub fn new() -> GrowingGrid<T> {
+        GrowingGrid {
+            allocated: None,
+            written: None,
+            arr: vec![],
+        }
+    }
+
+    fn index(allocated: &Bounds, x: i64, y: i64) -> usize {
+        (x - allocated.min_x) as usize + (y - allocated.min_y) as usize * allocated.width()
+    }
+
+    /// Make room for (x, y), copying the existing cells into a bigger allocation
+    fn grow_to(&mut self, x: i64, y: i64) {
+        let old = match self.allocated {
+            Some(old) => old,
+            None => {
+                self.allocated = Some(Bounds::point(x, y));
+                self.arr = vec![T::default()];
+                return;
+            }
+        };
+
+        let mut new = old.including(x, y);
+        if new.min_x < old.min_x {
+            new.min_x = new.min_x.min(old.min_x - old.width() as i64);
+        }
+        if new.max_x > old.max_x {
+            new.max_x = new.max_x.max(old.max_x + old.width() as i64);
+        }
+        if new.min_y < old.min_y {
+            new.min_y = new.min_y.min(old.min_y - old.height() as i64);
+        }
+        if new.max_y > old.max_y {
+            new.max_y = new.max_y.max(old.max_y + old.height() as i64);
+        }
+
+        let mut arr = vec![T::default(); new.width() * new.height()];
+        for (old_idx, (x, y)) in old.points().enumerate() {
+            arr[GrowingGrid::<T>::index(&new, x, y)] = std::mem::take(&mut self.arr[old_idx]);
+        }
+        self.arr = arr;
+        self.allocated = Some(new);
+    }
+}
+
+impl<T> Grid<T> for GrowingGrid<T>
+where
+    T: Clone + Default,
+{
+    fn cell(&self, x: i64, y: i64) -> Option<&T> {
+        let written = self.written?;
+        if !written.contains(x, y) {
+            return None;
+        }
+        let allocated = self.allocated?;
+        Some(&self.arr[GrowingGrid::<T>::index(&allocated, x, y)])
+    }
+
+    fn cell_mut(&mut self, x: i64, y: i64) -> &mut T {
+        if !matches!(self.allocated, Some(a) if a.contains(x, y)) {
+            self.grow_to(x, y);
+        }
+        self.written = Some(match self.written {
+            Some(written) => written.including(x, y),
+            None => Bounds::point(x, y),
+        });
+
+        let allocated = self.allocated.expect("Grid was just grown to fit the cell");
+        &mut self.arr[GrowingGrid::<T>::index(&allocated, x, y)]
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        self.written
+    }
+
+    fn cells(&self) -> Box<dyn Iterator<Item = ((i64, i64), &T)> + '_> {
+        match (self.written, self.allocated) {
+            (Some(written), Some(allocated)) => {
+                Box::new(written.points().map(move |(x, y)| {
+                    ((x, y), &self.arr[GrowingGrid::<T>::index(&allocated, x, y)])
+                }))
+            }
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Writes the same pattern into any kind of grid, to check that they all behave the same
+    fn draw_cross(grid: &mut impl Grid<u8>, x: i64, y: i64) {
+        grid.set_cell(x, y, 5);
+        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+            *grid.cell_mut(x + dx, y + dy) += 1;
+        }
+    }
+
+    #[test]
+    pub fn grids_agree() {
+        let mut array: ArrayGrid<u8> = ArrayGrid::create(5, 5);
+        let mut sparse = SparseGrid::new();
+        let mut growing = GrowingGrid::new();
+        draw_cross(&mut array, 2, 2);
+        draw_cross(&mut sparse, 2, 2);
+        draw_cross(&mut growing, 2, 2);
+
+        assert_eq!(Some(&5), sparse.cell(2, 2));
+        assert_eq!(Some(&1), growing.cell(3, 2));
+        assert_eq!(5, sparse.len());
+
+        let expected = Bounds {
+            min_x: 1,
+            min_y: 1,
+            max_x: 3,
+            max_y: 3,
+        };
+        assert_eq!(Some(expected), sparse.bounds());
+        assert_eq!(Some(expected), growing.bounds());
+
+        let array_text = array.to_array_grid().to_string();
+        assert_eq!("00000\n00100\n01510\n00100\n00000", array_text);
+        assert_eq!("010\n151\n010", sparse.to_array_grid().to_string());
+        assert_eq!("010\n151\n010", growing.to_array_grid().to_string());
+    }
+
+    #[test]
+    pub fn sparse_grid_works() {
+        let mut grid: SparseGrid<u8> = SparseGrid::new();
+        assert!(grid.is_empty());
+        assert_eq!(None, grid.bounds());
+
+        grid.set_cell(-1_000_000, 7, 1);
+        grid.set_cell(1_000_000, -7, 2);
+        assert_eq!(None, grid.cell(0, 0));
+        assert_eq!(Some(&2), grid.cell(1_000_000, -7));
+        assert_eq!(2_000_001, grid.bounds().unwrap().width());
+        assert_eq!(2, grid.cells().count());
+    }
+
+    #[test]
+    pub fn growing_grid_grows_in_every_direction() {
+        let mut grid: GrowingGrid<i64> = GrowingGrid::new();
+        assert_eq!(0, grid.cells().count());
+
+        let points = [
+            (0, 0),
+            (3, 0),
+            (-4, 0),
+            (0, 5),
+            (0, -6),
+            (-10, -10),
+            (10, 10),
+        ];
+        for &(x, y) in &points {
+            grid.set_cell(x, y, x * 100 + y);
+        }
+        for &(x, y) in &points {
+            assert_eq!(Some(&(x * 100 + y)), grid.cell(x, y));
+        }
+        assert_eq!(Some(&0), grid.cell(1, 1));
+        assert_eq!(None, grid.cell(11, 0));
+
+        let bounds = grid.bounds().unwrap();
+        assert_eq!((-10, -10), (bounds.min_x, bounds.min_y));
+        assert_eq!(21 * 21, grid.cells().count());
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn array_grid_cant_grow() {
+        let mut grid: ArrayGrid<u8> = ArrayGrid::create(2, 2);
+        grid.set_cell(2, 0, 1);
+    }
+}
@@ -1,48 +1,132 @@
+use crate::answer::Answer;
 use crate::array_grid::ArrayGrid;
+use crate::error::{parse_lines, parse_number, LineError, PuzzleResult};
+use crate::grid::Grid;
+use crate::point::Point;
+use crate::solver::Solver;
 
-pub fn run(input: String) -> Result<String, String> {
-    let mut grid: ArrayGrid<VentSquare> = ArrayGrid::create_square(1000);
+const NAME: &str = "hydrothermal";
 
-    input
-        .lines()
-        .map(parse_line)
-        .flat_map(|ls| ls.get_all_points())
-        .for_each(|point| {
-            grid.get_mut(point.x, point.y).num_vents += 1;
-        });
+pub struct Hydrothermal;
 
-    let overlaps = grid.iter().filter(|vs| vs.num_vents > 1).count();
+impl Solver for Hydrothermal {
+    type Input = Vec<LineSegment>;
 
-    Ok(format!("overlaps: {}", overlaps))
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn day(&self) -> usize {
+        5
+    }
+
+    fn input_file(&self) -> &'static str {
+        "hydrothermal.txt"
+    }
+
+    fn parse(&self, input: &str) -> PuzzleResult<Vec<LineSegment>> {
+        parse_lines(NAME, input, parse_line).collect()
+    }
+
+    fn part1(&self, line_segments: &Vec<LineSegment>) -> PuzzleResult<Answer> {
+        count_overlaps(line_segments, false)
+    }
+
+    fn part2(&self, line_segments: &Vec<LineSegment>) -> PuzzleResult<Answer> {
+        count_overlaps(line_segments, true)
+    }
+}
+
+fn count_overlaps(line_segments: &[LineSegment], include_diagonals: bool) -> PuzzleResult<Answer> {
+    let grid = vent_map(line_segments, include_diagonals);
+    let overlaps = grid.count_where(|vs| vs.num_vents > 1);
+
+    Ok(Answer::new("overlaps", overlaps))
+}
+
+/// Count the vents at every point on the sea floor, in a grid just big enough to hold all of the line segments.
+/// It can be drawn with `render()`, or as an image with eg
+/// `grid.write_ppm(&mut file, |vs| Palette::heat().color(vs.num_vents as usize))`
+pub fn vent_map(line_segments: &[LineSegment], include_diagonals: bool) -> ArrayGrid<VentSquare> {
+    let width = line_segments
+        .iter()
+        .map(|ls| ls.start.x.max(ls.end.x) as usize + 1)
+        .max()
+        .unwrap_or(0);
+    let height = line_segments
+        .iter()
+        .map(|ls| ls.start.y.max(ls.end.y) as usize + 1)
+        .max()
+        .unwrap_or(0);
+    let mut grid: ArrayGrid<VentSquare> = ArrayGrid::create(width, height);
+    mark_vents(&mut grid, line_segments, include_diagonals);
+    grid
+}
+
+/// Add a vent to the grid for every point on each line segment.
+/// Any kind of `Grid` works, though an `ArrayGrid` has to be big enough to hold all of the points.
+pub fn mark_vents(
+    grid: &mut impl Grid<VentSquare>,
+    line_segments: &[LineSegment],
+    include_diagonals: bool,
+) {
+    for line_segment in line_segments {
+        if include_diagonals || !line_segment.is_diagonal() {
+            for point in line_segment.get_all_points() {
+                grid.cell_mut(point.x, point.y).num_vents += 1;
+            }
+        }
+    }
 }
 
-fn parse_line(line: &str) -> LineSegment {
+fn parse_line(line: &str) -> Result<LineSegment, LineError> {
     let list: Vec<&str> = line.split(" -> ").collect();
     if let [start_pair, end_pair] = &list[..] {
-        LineSegment {
-            start: parse_coordinate(start_pair),
-            end: parse_coordinate(end_pair),
+        let segment = LineSegment {
+            start: parse_coordinate(line, start_pair)?,
+            end: parse_coordinate(line, end_pair)?,
+        };
+
+        let width = segment.start.x.abs_diff(segment.end.x);
+        let height = segment.start.y.abs_diff(segment.end.y);
+        if segment.is_diagonal() && width != height {
+            Err(LineError::new(
+                line,
+                line,
+                "lines must be horizontal, vertical or at 45 degrees",
+            ))
+        } else {
+            Ok(segment)
         }
     } else {
-        panic!("Unable to parse {} as a LineSegment", line);
+        Err(LineError::new(
+            line,
+            line,
+            "expected a line segment like \"1,2 -> 3,4\"",
+        ))
     }
 }
 
-fn parse_coordinate(raw_coord: &str) -> Coordinate {
+fn parse_coordinate(line: &str, raw_coord: &str) -> Result<Point, LineError> {
     let list: Vec<&str> = raw_coord.split(',').collect();
     if let [raw_x, raw_y] = &list[..] {
-        Coordinate {
-            x: raw_x.parse::<usize>().unwrap(),
-            y: raw_y.parse::<usize>().unwrap(),
-        }
+        //Parsed unsigned, since the vent map starts at (0, 0)
+        Ok(Point::new(
+            parse_number::<u32>(line, raw_x)?.into(),
+            parse_number::<u32>(line, raw_y)?.into(),
+        ))
     } else {
-        panic!("Unable to parse {} as a Coordinate", raw_coord);
+        Err(LineError::new(
+            line,
+            raw_coord,
+            "expected a coordinate like \"1,2\"",
+        ))
     }
 }
 
 #[derive(Clone, Default, Debug)]
-struct VentSquare {
-    num_vents: u16,
+pub struct VentSquare {
+    pub num_vents: u16,
 }
 
 impl std::fmt::Display for VentSquare {
@@ -52,44 +136,36 @@ impl std::fmt::Display for VentSquare {
 }
 
 #[derive(Eq, PartialEq, Debug)]
-struct Coordinate {
-    x: usize,
-    y: usize,
+pub struct LineSegment {
+    start: Point,
+    end: Point,
 }
 
-impl std::fmt::Display for Coordinate {
-    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        write!(fmt, "(x:{}, y:{})", self.x, self.y)
+impl LineSegment {
+    fn is_diagonal(&self) -> bool {
+        self.start.x != self.end.x && self.start.y != self.end.y
     }
-}
-
-#[derive(Eq, PartialEq, Debug)]
-struct LineSegment {
-    start: Coordinate,
-    end: Coordinate,
-}
 
-impl LineSegment {
-    fn get_all_points(&self) -> Vec<Coordinate> {
+    fn get_all_points(&self) -> Vec<Point> {
         if self.start.x == self.end.x {
             safe_range_inclusive(self.start.y, self.end.y)
-                .map(|y| Coordinate { x: self.start.x, y })
+                .map(|y| Point::new(self.start.x, y))
                 .collect()
         } else if self.start.y == self.end.y {
             safe_range_inclusive(self.start.x, self.end.x)
-                .map(|x| Coordinate { x, y: self.start.y })
+                .map(|x| Point::new(x, self.start.y))
                 .collect()
         } else {
             safe_range_inclusive(self.start.x, self.end.x)
                 .zip(safe_range_inclusive(self.start.y, self.end.y))
-                .map(|(x, y)| Coordinate { x, y })
+                .map(|(x, y)| Point::new(x, y))
                 .collect()
         }
     }
 }
 
-fn safe_range_inclusive(a: usize, b: usize) -> impl Iterator<Item = usize> {
-    let x: Box<dyn Iterator<Item = usize>>;
+fn safe_range_inclusive(a: i64, b: i64) -> impl Iterator<Item = i64> {
+    let x: Box<dyn Iterator<Item = i64>>;
     if b > a {
         x = Box::new(a..=b)
     } else {
@@ -111,75 +187,162 @@ impl std::fmt::Display for LineSegment {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::grid::{GrowingGrid, SparseGrid};
+
+    const SAMPLE: &str = "0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2";
+
+    #[test]
+    pub fn sample_part1() {
+        let line_segments = Hydrothermal.parse(SAMPLE).unwrap();
+        assert_eq!(
+            Ok(Answer::new("overlaps", 5u32)),
+            Hydrothermal.part1(&line_segments)
+        );
+    }
+
+    #[test]
+    pub fn sample_vent_map() {
+        let line_segments = Hydrothermal.parse(SAMPLE).unwrap();
+        let grid = vent_map(&line_segments, false);
+        let diagram = grid
+            .render_with(|vs| match vs.num_vents {
+                0 => String::from("."),
+                n => n.to_string(),
+            })
+            .to_string();
+
+        let expected = [
+            ".......1..",
+            "..1....1..",
+            "..1....1..",
+            ".......1..",
+            ".112111211",
+            "..........",
+            "..........",
+            "..........",
+            "..........",
+            "222111....",
+        ];
+        assert_eq!(expected.join("\n"), diagram);
+    }
+
+    #[test]
+    pub fn any_grid_can_hold_vents() {
+        let line_segments = Hydrothermal.parse(SAMPLE).unwrap();
+        let mut sparse: SparseGrid<VentSquare> = SparseGrid::new();
+        let mut growing: GrowingGrid<VentSquare> = GrowingGrid::new();
+        mark_vents(&mut sparse, &line_segments, true);
+        mark_vents(&mut growing, &line_segments, true);
+
+        let count =
+            |grid: &dyn Grid<VentSquare>| grid.cells().filter(|(_, vs)| vs.num_vents > 1).count();
+        assert_eq!(12, count(&sparse));
+        assert_eq!(12, count(&growing));
+        assert_eq!(
+            vent_map(&line_segments, true).to_string(),
+            growing.to_array_grid().to_string()
+        );
+    }
+
+    #[test]
+    pub fn sample_part2() {
+        let line_segments = Hydrothermal.parse(SAMPLE).unwrap();
+        assert_eq!(
+            Ok(Answer::new("overlaps", 12u32)),
+            Hydrothermal.part2(&line_segments)
+        );
+    }
+
+    #[test]
+    pub fn parse_line_errors() {
+        let err = parse_line("0,9 -> 5,x").unwrap_err();
+        assert_eq!(10, err.column);
+        assert_eq!("x", err.text);
+
+        let err = parse_line("0,9 -> 59").unwrap_err();
+        assert_eq!(8, err.column);
+
+        parse_line("0,9 - 5,9").expect_err("Not a line segment");
+        parse_line("0,0 -> 1,2").expect_err("Not at 45 degrees");
+    }
 
     #[test]
     pub fn linesegment_get_all_points_vertical_works() {
         let ls = LineSegment {
-            start: Coordinate { x: 1, y: 1 },
-            end: Coordinate { x: 1, y: 3 },
+            start: Point::new(1, 1),
+            end: Point::new(1, 3),
         };
         let points = ls.get_all_points();
         points.iter().for_each(|p| println!("{:?}", p));
 
-        assert_eq!(points[0], Coordinate { x: 1, y: 1 });
-        assert_eq!(points[1], Coordinate { x: 1, y: 2 });
-        assert_eq!(points[2], Coordinate { x: 1, y: 3 });
+        assert_eq!(points[0], Point::new(1, 1));
+        assert_eq!(points[1], Point::new(1, 2));
+        assert_eq!(points[2], Point::new(1, 3));
     }
 
     #[test]
     pub fn linesegment_get_all_points_horizontal_works() {
         let ls = LineSegment {
-            start: Coordinate { x: 1, y: 1 },
-            end: Coordinate { x: 4, y: 1 },
+            start: Point::new(1, 1),
+            end: Point::new(4, 1),
         };
         let points = ls.get_all_points();
         points.iter().for_each(|p| println!("{:?}", p));
 
-        assert_eq!(points[0], Coordinate { x: 1, y: 1 });
-        assert_eq!(points[1], Coordinate { x: 2, y: 1 });
-        assert_eq!(points[2], Coordinate { x: 3, y: 1 });
-        assert_eq!(points[3], Coordinate { x: 4, y: 1 });
+        assert_eq!(points[0], Point::new(1, 1));
+        assert_eq!(points[1], Point::new(2, 1));
+        assert_eq!(points[2], Point::new(3, 1));
+        assert_eq!(points[3], Point::new(4, 1));
     }
 
     #[test]
     pub fn linesegment_get_all_points_horizontal_works_desc() {
         let ls = LineSegment {
-            start: Coordinate { x: 4, y: 1 },
-            end: Coordinate { x: 1, y: 1 },
+            start: Point::new(4, 1),
+            end: Point::new(1, 1),
         };
         let points = ls.get_all_points();
         points.iter().for_each(|p| println!("{:?}", p));
 
-        assert_eq!(points[0], Coordinate { x: 4, y: 1 });
-        assert_eq!(points[1], Coordinate { x: 3, y: 1 });
-        assert_eq!(points[2], Coordinate { x: 2, y: 1 });
-        assert_eq!(points[3], Coordinate { x: 1, y: 1 });
+        assert_eq!(points[0], Point::new(4, 1));
+        assert_eq!(points[1], Point::new(3, 1));
+        assert_eq!(points[2], Point::new(2, 1));
+        assert_eq!(points[3], Point::new(1, 1));
     }
 
     #[test]
     pub fn linesegment_get_all_points_diagonal_works() {
         let ls = LineSegment {
-            start: Coordinate { x: 1, y: 3 },
-            end: Coordinate { x: 3, y: 1 },
+            start: Point::new(1, 3),
+            end: Point::new(3, 1),
         };
         let points = ls.get_all_points();
         points.iter().for_each(|p| println!("{:?}", p));
 
-        assert_eq!(points[0], Coordinate { x: 1, y: 3 });
-        assert_eq!(points[1], Coordinate { x: 2, y: 2 });
-        assert_eq!(points[2], Coordinate { x: 3, y: 1 });
+        assert_eq!(points[0], Point::new(1, 3));
+        assert_eq!(points[1], Point::new(2, 2));
+        assert_eq!(points[2], Point::new(3, 1));
     }
 
     #[test]
     pub fn safe_range_inclusivee_asc() {
-        let r: Vec<usize> = safe_range_inclusive(1, 10).collect();
+        let r: Vec<i64> = safe_range_inclusive(1, 10).collect();
         assert_eq!(r.first().unwrap(), &1);
         assert_eq!(r.last().unwrap(), &10);
     }
 
     #[test]
     pub fn safe_range_inclusive_desc() {
-        let r: Vec<usize> = safe_range_inclusive(10, 1).collect();
+        let r: Vec<i64> = safe_range_inclusive(10, 1).collect();
         assert_eq!(r.first().unwrap(), &10);
         assert_eq!(r.last().unwrap(), &1);
     }
@@ -1,39 +1,95 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, LineError, PuzzleResult};
+use crate::solver::Solver;
 use itertools::Itertools;
 
-pub fn run(input: String) -> Result<String, String> {
-    let mut age_tracker = AgeTracker::new(9, 6);
-    input
-        .split(',')
-        .map(|c| c.parse::<usize>().unwrap())
-        .for_each(|a| age_tracker.increment(a));
+const NAME: &str = "lanternfish";
+const NUM_AGES: usize = 9;
+
+pub struct Lanternfish;
+
+impl Solver for Lanternfish {
+    type Input = Vec<usize>;
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn day(&self) -> usize {
+        6
+    }
+
+    fn input_file(&self) -> &'static str {
+        "lanternfish.txt"
+    }
+
+    fn parse(&self, input: &str) -> PuzzleResult<Vec<usize>> {
+        let mut ages: Vec<usize> = vec![];
+        for line in parse_lines(NAME, input, parse_ages) {
+            ages.extend(line?);
+        }
+        Ok(ages)
+    }
+
+    fn part1(&self, ages: &Vec<usize>) -> PuzzleResult<Answer> {
+        Ok(Answer::new("fish", simulate(ages, 80)))
+    }
+
+    fn part2(&self, ages: &Vec<usize>) -> PuzzleResult<Answer> {
+        Ok(Answer::new("fish", simulate(ages, 256)))
+    }
+}
+
+/// Returns the number of fish after the given number of days
+pub fn simulate(ages: &[usize], days: usize) -> u64 {
+    let mut age_tracker = AgeTracker::new(NUM_AGES, 6);
+    ages.iter().for_each(|&a| age_tracker.increment(a));
 
     dump_ages(&age_tracker);
-    for _ in 0..256 {
+    for _ in 0..days {
         age_tracker.advance();
         dump_ages(&age_tracker);
     }
 
-    let total_fish: u64 = age_tracker.iter().sum();
+    age_tracker.iter().sum()
+}
 
-    Ok(format!("fish: {}", total_fish))
+fn parse_ages(line: &str) -> Result<Vec<usize>, LineError> {
+    line.split(',')
+        .map(|token| {
+            let age = parse_number(line, token.trim())?;
+            if age < NUM_AGES {
+                Ok(age)
+            } else {
+                Err(LineError::new(
+                    line,
+                    token.trim(),
+                    format!("fish can't be older than {}", NUM_AGES - 1),
+                ))
+            }
+        })
+        .collect()
 }
 
 fn dump_ages(tracker: &AgeTracker) {
-    println!(
+    log::debug!(
         "{}  total={}",
         tracker.iter().join(", "),
         tracker.iter().sum::<u64>()
     );
 }
 
-struct AgeTracker {
+/// Counts how many fish there are of each age, in a ring buffer so that ageing them all by a day
+/// only needs to move the index of age zero rather than shuffle every count along.
+pub struct AgeTracker {
     storage: Vec<u64>,
     zero_age_idx: usize,
     breeding_age: usize,
 }
 
 impl AgeTracker {
-    fn new(num_ages: usize, breeding_age: usize) -> AgeTracker {
+    /// Tracks ages from 0 to `num_ages - 1`, with new parents being reset to `breeding_age`
+    pub fn new(num_ages: usize, breeding_age: usize) -> AgeTracker {
         let vec: Vec<u64> = vec![0; num_ages];
 
         AgeTracker {
@@ -43,7 +99,8 @@ impl AgeTracker {
         }
     }
 
-    fn advance(&mut self) {
+    /// Age every fish by one day, with any at age zero each giving birth to a new fish
+    pub fn advance(&mut self) {
         let num_giving_birth = self.get(0);
         if self.zero_age_idx == self.storage.len() - 1 {
             self.zero_age_idx = 0;
@@ -53,15 +110,16 @@ impl AgeTracker {
         self.add(self.breeding_age, num_giving_birth);
     }
 
-    fn get(&self, age: usize) -> u64 {
+    /// The number of fish of the given age
+    pub fn get(&self, age: usize) -> u64 {
         self.storage[self.get_index(age)]
     }
 
-    fn increment(&mut self, age: usize) {
+    pub fn increment(&mut self, age: usize) {
         self.add(age, 1);
     }
 
-    fn add(&mut self, age: usize, amt: u64) {
+    pub fn add(&mut self, age: usize, amt: u64) {
         let idx = self.get_index(age);
         self.storage[idx] += amt;
     }
@@ -74,7 +132,8 @@ impl AgeTracker {
         idx
     }
 
-    fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+    /// The number of fish of each age, starting from zero
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
         let mut index: usize = 0;
         std::iter::from_fn(move || {
             if index < self.storage.len() {
@@ -92,6 +151,26 @@ impl AgeTracker {
 mod test {
     use super::*;
 
+    #[test]
+    pub fn sample_works() {
+        let sample = Lanternfish.parse("3,4,3,1,2").unwrap();
+        assert_eq!(26, simulate(&sample, 18));
+        assert_eq!(Ok(Answer::new("fish", 5934u64)), Lanternfish.part1(&sample));
+        assert_eq!(
+            Ok(Answer::new("fish", 26984457539u64)),
+            Lanternfish.part2(&sample)
+        );
+    }
+
+    #[test]
+    pub fn parse_ages_errors() {
+        let err = parse_ages("3,4,9,1").unwrap_err();
+        assert_eq!(5, err.column);
+        assert_eq!("9", err.text);
+
+        parse_ages("3,4,,1").expect_err("Missing a fish");
+    }
+
     #[test]
     pub fn age_tracker_works() {
         let mut tracker = AgeTracker::new(5, 3);
@@ -0,0 +1,34 @@
+//! Solutions to the Advent of Code 2021 puzzles, along with the grids, parsers and reporting used to build them.
+//!
+//! Every puzzle implements `Solver`, and is listed in `solver::PUZZLES` for running by name or by day.
+
+pub mod answer;
+pub mod array_grid;
+pub mod automaton;
+pub mod bench;
+pub mod binary;
+pub mod dive;
+pub mod error;
+pub mod grid;
+pub mod hydrothermal;
+pub mod lanternfish;
+pub mod pathfinding;
+pub mod point;
+pub mod regions;
+pub mod render;
+pub mod report;
+pub mod seven_segment;
+pub mod smoke_basin;
+pub mod solver;
+pub mod sonar;
+pub mod squid;
+pub mod transform;
+pub mod verify;
+pub mod whales;
+
+pub use answer::{Answer, Value};
+pub use array_grid::ArrayGrid;
+pub use error::{PuzzleError, PuzzleResult};
+pub use grid::{Grid, GrowingGrid, SparseGrid};
+pub use point::{Compass, Direction, Point};
+pub use solver::{Part, Puzzle, Solver, PUZZLES};
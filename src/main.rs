@@ -1,155 +1,543 @@
-mod array_grid;
-mod binary;
-mod dive;
-mod hydrothermal;
-mod lanternfish;
-mod seven_segment;
-mod smoke_basin;
-mod sonar;
-mod squid;
-mod whales;
-
+use advent_2021::error::{PuzzleError, PuzzleResult};
+use advent_2021::report::{self, Format};
+use advent_2021::solver::{self, Part, Puzzle, PuzzleRun};
+use advent_2021::sonar::{self, DepthCounter, DirectionStats, Sonar, WindowStat};
+use advent_2021::{bench, verify};
 use clap::{AppSettings, ArgGroup, Parser};
+use itertools::Itertools;
 use std::fs::File;
 use std::io::prelude::*;
-use std::path::Path;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+/// Looked for in the working directory and then next to the executable when neither `--inputs-dir` nor
+/// `ADVENT_INPUTS_DIR` are given
+const DEFAULT_INPUTS_DIR: &str = "inputs";
 
 fn main() {
     env_logger::init();
     let args = Args::parse();
+    if let Err(e) = check_stream_options(&args) {
+        eprintln!("{}", e);
+        exit(2);
+    }
 
-    let puzzle_info: PuzzleInfo;
-
-    if let Some(day) = args.day {
-        puzzle_info = get_puzzle_by_day_number(day);
-    } else if let Some(p) = args.puzzle {
-        puzzle_info = get_puzzle_info(&p);
+    let succeeded = if args.verify {
+        run_verify(&args)
+    } else if args.stream {
+        run_stream(&args)
+    } else if let Some(iterations) = args.bench {
+        run_bench(&args, iterations)
+    } else if args.all {
+        run_all(&args)
     } else {
-        puzzle_info = get_latest_puzzle();
+        run_single(&args)
+    };
+
+    if !succeeded {
+        exit(1);
     }
-    let path = Path::new("inputs").join(puzzle_info.input);
-    let path_display = path.display();
-    //println!("Reading input from {}", path_display);
+}
 
-    let mut file = match File::open(&path) {
-        Err(why) => panic!("couldn't open {}: {}", path_display, why),
-        Ok(file) => file,
+/// Run the one puzzle selected by the arguments, printing each answer
+fn run_single(args: &Args) -> bool {
+    let puzzle = match select_puzzle(args) {
+        Ok(puzzle) => puzzle,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
     };
 
-    // Read the file contents into a string, returns `io::Result<usize>`
-    let mut s = String::new();
-    if let Err(why) = file.read_to_string(&mut s) {
-        panic!("couldn't read {}: {}", path_display, why);
+    let run = load_input(args, puzzle).and_then(|input| puzzle.run(&input, args.part.parts()));
+    if args.format != Format::Text {
+        let runs = [(puzzle, run)];
+        print_records(args, &runs);
+        return report::print_errors(&runs);
     }
 
-    let result = (*puzzle_info.func)(s);
-    match result {
-        Ok(s) => println!("{}", s),
-        Err(e) => println!("{}", e),
+    let run = match run {
+        Ok(run) => run,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+
+    let mut succeeded = true;
+    for part_run in &run.parts {
+        match &part_run.answer {
+            Ok(s) => println!("{} part {}: {}", puzzle.name(), part_run.part, s),
+            Err(e) => {
+                eprintln!("{}", e);
+                succeeded = false;
+            }
+        }
     }
+    succeeded
 }
 
-fn get_puzzle_by_day_number(day: usize) -> PuzzleInfo {
-    if let Some(p) = PUZZLES.get(day - 1) {
-        get_puzzle_info(p)
-    } else {
-        panic!("Could not find puzzle for day {}", day);
+/// Run every puzzle, and print a table summarising the answers and timings
+fn run_all(args: &Args) -> bool {
+    let runs: Vec<(&dyn Puzzle, PuzzleResult<PuzzleRun>)> = solver::PUZZLES
+        .iter()
+        .map(|&puzzle| {
+            let run =
+                load_input(args, puzzle).and_then(|input| puzzle.run(&input, args.part.parts()));
+            (puzzle, run)
+        })
+        .collect();
+
+    match args.format {
+        Format::Text => report::print_summary(&runs, args.part.parts()),
+        _ => print_records(args, &runs),
     }
+    report::print_errors(&runs)
 }
 
-fn get_latest_puzzle() -> PuzzleInfo {
-    let pz = PUZZLES;
-    let last = pz
-        .last()
-        .expect("Puzzles list was unexpectedly empty, bad programmer!");
-    get_puzzle_info(last)
+/// Print the runs in one of the machine-readable formats
+fn print_records(args: &Args, runs: &[(&dyn Puzzle, PuzzleResult<PuzzleRun>)]) {
+    let records = report::records(runs, args.part.parts());
+    match args.format {
+        Format::Json => report::print_json(&records),
+        Format::Csv => report::print_csv(&records),
+        Format::Text => unreachable!("Text output doesn't use records"),
+    }
 }
 
-fn get_puzzle_info(puzzle: &Puzzle) -> PuzzleInfo {
-    match puzzle {
-        Puzzle::Sonar => PuzzleInfo {
-            input: String::from("sonar.txt"),
-            func: &sonar::run,
-        },
-        Puzzle::Dive => PuzzleInfo {
-            input: String::from("dive.txt"),
-            func: &dive::run,
-        },
-        Puzzle::Binary => PuzzleInfo {
-            input: String::from("binary.txt"),
-            func: &binary::run,
-        },
-        Puzzle::Squid => PuzzleInfo {
-            input: String::from("squid.txt"),
-            func: &squid::run,
-        },
-        Puzzle::Hydrothermal => PuzzleInfo {
-            input: String::from("hydrothermal.txt"),
-            func: &hydrothermal::run,
-        },
-        Puzzle::Lanternfish => PuzzleInfo {
-            input: String::from("lanternfish.txt"),
-            func: &lanternfish::run,
-        },
-        Puzzle::Whales => PuzzleInfo {
-            input: String::from("whales.txt"),
-            func: &whales::run,
-        },
-        Puzzle::SevenSegment => PuzzleInfo {
-            input: String::from("seven_segment.txt"),
-            func: &seven_segment::run,
+/// Check the answers of the selected puzzle, or every puzzle if none was selected, against the known-good answers
+fn run_verify(args: &Args) -> bool {
+    let puzzles: Vec<&dyn Puzzle> = if args.day.is_some() || args.puzzle.is_some() {
+        match select_puzzle(args) {
+            Ok(puzzle) => vec![puzzle],
+            Err(e) => {
+                eprintln!("{}", e);
+                return false;
+            }
+        }
+    } else {
+        solver::PUZZLES.to_vec()
+    };
+
+    let answers_path = match &args.answers {
+        Some(path) => path.clone(),
+        None => match inputs_dir(args) {
+            Ok(dir) => dir.join("answers.toml"),
+            Err(e) => {
+                eprintln!("{}", e);
+                return false;
+            }
         },
-        Puzzle::SmokeBasin => PuzzleInfo {
-            input: String::from("smoke_basin.txt"),
-            func: &smoke_basin::run,
+    };
+    let answers = match verify::Answers::load(&answers_path) {
+        Ok(answers) => answers,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+
+    let mut failures = 0;
+    for puzzle in puzzles {
+        let run = load_input(args, puzzle).and_then(|input| puzzle.run(&input, args.part.parts()));
+        for (part, verdict) in verify::check(&answers, puzzle, run, args.part.parts()) {
+            if verdict.is_failure() {
+                failures += 1;
+            }
+            println!("{} part {}: {}", puzzle.name(), part, verdict);
+        }
+    }
+
+    if failures > 0 {
+        eprintln!("{} answer(s) did not match", failures);
+    }
+    failures == 0
+}
+
+/// Time the selected puzzle, or every puzzle with `--all`, then compare against and save the baseline if asked to
+fn run_bench(args: &Args, iterations: usize) -> bool {
+    if iterations == 0 {
+        eprintln!("--bench needs at least 1 iteration");
+        return false;
+    }
+
+    let puzzles: Vec<&dyn Puzzle> = if args.all {
+        solver::PUZZLES.to_vec()
+    } else {
+        match select_puzzle(args) {
+            Ok(puzzle) => vec![puzzle],
+            Err(e) => {
+                eprintln!("{}", e);
+                return false;
+            }
+        }
+    };
+
+    let baseline = match args.baseline.as_deref().map(bench::Baseline::load) {
+        Some(Ok(baseline)) => Some(baseline),
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            return false;
+        }
+        None => None,
+    };
+
+    let mut succeeded = true;
+    let mut benchmarks = Vec::with_capacity(puzzles.len());
+    for puzzle in puzzles {
+        let benchmark = load_input(args, puzzle)
+            .and_then(|input| bench::bench(puzzle, &input, args.part.parts(), iterations));
+        match benchmark {
+            Ok(benchmark) => benchmarks.push(benchmark),
+            Err(e) => {
+                eprintln!("{}", e);
+                succeeded = false;
+            }
+        }
+    }
+
+    let threshold = args.threshold.unwrap_or(bench::DEFAULT_THRESHOLD_PERCENT);
+    succeeded &= bench::print_benchmarks(&benchmarks, baseline.as_ref(), threshold);
+
+    if let Some(path) = &args.save_baseline {
+        //Keep the times of any puzzles that weren't benchmarked this time
+        let saved = if path.exists() {
+            bench::Baseline::load(path)
+        } else {
+            Ok(bench::Baseline::default())
+        };
+        let saved = saved.map_err(|e| e.to_string()).and_then(|mut saved| {
+            benchmarks.iter().for_each(|b| saved.record(b));
+            saved.save(path)
+        });
+        if let Err(e) = saved {
+            eprintln!("{}", e);
+            succeeded = false;
+        }
+    }
+    succeeded
+}
+
+/// Follow the sonar depths as they're read, printing the running counts after each one, and a summary of
+/// the changes in direction at the end.
+/// Malformed lines are reported and skipped, but still make it fail at the end.
+fn run_stream(args: &Args) -> bool {
+    let path = match &args.input {
+        Some(path) => path.clone(),
+        None => match inputs_dir(args) {
+            Ok(dir) => dir.join(Sonar.input_file()),
+            Err(e) => {
+                eprintln!("{}", e);
+                return false;
+            }
         },
-        Puzzle::Latest => get_latest_puzzle(),
+    };
+    let reader: Box<dyn BufRead> = if path == Path::new("-") {
+        Box::new(BufReader::new(std::io::stdin()))
+    } else {
+        match File::open(&path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(why) => {
+                eprintln!(
+                    "{}",
+                    PuzzleError::Input {
+                        path: path.display().to_string(),
+                        reason: why.to_string(),
+                    }
+                );
+                return false;
+            }
+        }
+    };
+
+    let sizes = if args.window_sizes.is_empty() {
+        vec![sonar::WINDOW_SIZE]
+    } else {
+        args.window_sizes.clone()
+    };
+    if sizes.contains(&0) {
+        eprintln!("--window-sizes must all be at least 1");
+        return false;
+    }
+    let counter = DepthCounter::with_windows(&sizes, args.window_stat.unwrap_or(WindowStat::Sum));
+
+    let mut directions = DirectionStats::new(args.noise_tolerance.unwrap_or(0));
+    let mut errors = 0;
+    let mut depths = sonar::stream(reader, counter);
+    while let Some(result) = depths.next() {
+        if let Ok(reading) = &result {
+            directions.push(reading.line, reading.depth);
+        }
+        match result {
+            Ok(reading) => println!(
+                "{}: increases={}, {}",
+                reading.depth,
+                reading.increases,
+                depths
+                    .counter()
+                    .window_increases()
+                    .map(|(size, increases)| format!("window{}_increases={}", size, increases))
+                    .join(", ")
+            ),
+            Err(e) => {
+                eprintln!("{}", e);
+                errors += 1;
+            }
+        }
+    }
+
+    print!("{}", directions);
+
+    if errors > 0 {
+        eprintln!("{} line(s) could not be read", errors);
+    }
+    errors == 0
+}
+
+/// The sonar options only make sense with `--stream`, but clap doesn't enforce `requires = "stream"` when
+/// an argument that conflicts with `--stream` is given too, eg `-d 1 --window-sizes 2`
+fn check_stream_options(args: &Args) -> Result<(), String> {
+    let given = [
+        ("--window-sizes", !args.window_sizes.is_empty()),
+        ("--window-stat", args.window_stat.is_some()),
+        ("--noise-tolerance", args.noise_tolerance.is_some()),
+    ];
+    match given.iter().find(|(_, given)| *given) {
+        Some((name, _)) if !args.stream => Err(format!("{} can only be used with --stream", name)),
+        _ => Ok(()),
     }
 }
 
-struct PuzzleInfo {
-    input: String,
-    //Using `'static` lifetime since this is basically a commandline argument anyway
-    func: &'static dyn Fn(String) -> Result<String, String>,
+/// Read the input for a puzzle, from `--input` if it was given, or otherwise the puzzle's default file in the inputs directory
+fn load_input(args: &Args, puzzle: &dyn Puzzle) -> PuzzleResult<String> {
+    match &args.input {
+        Some(path) if path == Path::new("-") => read_stdin(),
+        Some(path) => read_input(path),
+        None => read_input(&inputs_dir(args)?.join(puzzle.input_file())),
+    }
+}
+
+/// The directory with the default input file for each puzzle: `--inputs-dir` (or `ADVENT_INPUTS_DIR`) if it was
+/// given, otherwise `inputs` in the working directory, or failing that `inputs` next to the executable
+fn inputs_dir(args: &Args) -> PuzzleResult<PathBuf> {
+    if let Some(dir) = &args.inputs_dir {
+        return Ok(dir.clone());
+    }
+    let local = PathBuf::from(DEFAULT_INPUTS_DIR);
+    if local.is_dir() {
+        return Ok(local);
+    }
+    let beside_exe = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(DEFAULT_INPUTS_DIR)));
+    match beside_exe {
+        Some(dir) if dir.is_dir() => Ok(dir),
+        _ => Err(PuzzleError::Input {
+            path: DEFAULT_INPUTS_DIR.to_string(),
+            reason: format!(
+                "tried {}{}, use --inputs-dir or ADVENT_INPUTS_DIR to say where it is",
+                std::env::current_dir()
+                    .map(|cwd| cwd.join(DEFAULT_INPUTS_DIR))
+                    .unwrap_or(local)
+                    .display(),
+                beside_exe
+                    .map(|dir| format!(" and {}", dir.display()))
+                    .unwrap_or_default()
+            ),
+        }),
+    }
+}
+
+fn read_input(path: &Path) -> PuzzleResult<String> {
+    let to_error = |why: std::io::Error| PuzzleError::Input {
+        path: path.display().to_string(),
+        reason: why.to_string(),
+    };
+
+    let mut file = File::open(path).map_err(to_error)?;
+    let mut s = String::new();
+    file.read_to_string(&mut s).map_err(to_error)?;
+    Ok(s)
+}
+
+fn read_stdin() -> PuzzleResult<String> {
+    let mut s = String::new();
+    std::io::stdin()
+        .read_to_string(&mut s)
+        .map_err(|why| PuzzleError::Input {
+            path: String::from("<stdin>"),
+            reason: why.to_string(),
+        })?;
+    Ok(s)
+}
+
+fn select_puzzle(args: &Args) -> Result<&'static dyn Puzzle, String> {
+    if let Some(day) = args.day {
+        solver::find_by_day(day).ok_or(format!("Could not find puzzle for day {}", day))
+    } else if let Some(name) = &args.puzzle {
+        solver::find_by_name(name).ok_or(format!(
+            "Could not find puzzle named \"{}\", expected one of: latest, {}",
+            name,
+            solver::PUZZLES.iter().map(|p| p.name()).join(", ")
+        ))
+    } else {
+        Ok(solver::latest())
+    }
 }
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about)]
 #[clap(global_setting(AppSettings::DeriveDisplayOrder))]
-#[clap(group(ArgGroup::new("puz").required(false).args(&["day", "puzzle"])))]
+#[clap(group(ArgGroup::new("puz").required(false).args(&["day", "puzzle", "all"])))]
 struct Args {
     #[clap(short, long, value_name = "DAY")]
     #[clap(parse(try_from_str))]
     //Run a puzzle by the day number of advent calendar (starting at 1)
     day: Option<usize>,
 
-    #[clap(arg_enum)]
+    #[clap(short, long, value_name = "NAME")]
+    //Run a puzzle by name, eg "smoke-basin", or "latest"
+    puzzle: Option<String>,
+
     #[clap(short, long)]
-    puzzle: Option<Puzzle>,
-}
-
-#[derive(Copy, Clone, PartialEq, Eq, clap::ArgEnum, Debug)]
-enum Puzzle {
-    Latest,
-    Sonar,
-    Dive,
-    Binary,
-    Squid,
-    Hydrothermal,
-    Lanternfish,
-    Whales,
-    SevenSegment,
-    SmokeBasin,
-}
-
-const PUZZLES: [Puzzle; 9] = [
-    Puzzle::Sonar,
-    Puzzle::Dive,
-    Puzzle::Binary,
-    Puzzle::Squid,
-    Puzzle::Hydrothermal,
-    Puzzle::Lanternfish,
-    Puzzle::Whales,
-    Puzzle::SevenSegment,
-    Puzzle::SmokeBasin,
-];
+    //Run every puzzle, and print a summary table of the answers and timings
+    all: bool,
+
+    #[clap(long, value_name = "1|2|BOTH", default_value = "both")]
+    #[clap(parse(try_from_str))]
+    //Which part(s) of the puzzle to run
+    part: PartSelection,
+
+    #[clap(long, value_name = "TEXT|JSON|CSV", default_value = "text")]
+    #[clap(parse(try_from_str))]
+    //How to print the results. JSON and CSV have one record per part, with the answer and timings
+    format: Format,
+
+    #[clap(long, conflicts_with_all = &["all", "input", "format"])]
+    //Check the answers of the selected puzzle, or every puzzle if none is selected, against the known-good answers
+    verify: bool,
+
+    #[clap(long, value_name = "FILE", requires = "verify")]
+    #[clap(parse(from_os_str))]
+    //TOML file of known-good answers to check against, defaults to answers.toml in the inputs directory
+    answers: Option<PathBuf>,
+
+    #[clap(long, value_name = "N", conflicts_with_all = &["verify", "format"])]
+    #[clap(parse(try_from_str))]
+    //Time the parse and solve phases of the selected puzzle (or every puzzle with --all) over N runs, and print statistics
+    bench: Option<usize>,
+
+    #[clap(long, value_name = "FILE", requires = "bench")]
+    #[clap(parse(from_os_str))]
+    //Compare the benchmark with the median times saved in this file, and fail if any phase got slower
+    baseline: Option<PathBuf>,
+
+    #[clap(long, value_name = "FILE", requires = "bench")]
+    #[clap(parse(from_os_str))]
+    //Save the median times from the benchmark to this file, keeping any other puzzles already in it
+    save_baseline: Option<PathBuf>,
+
+    #[clap(long, value_name = "PERCENT", requires = "baseline")]
+    #[clap(parse(try_from_str))]
+    //How much slower than the baseline a phase can get before it counts as a regression [default: 10]
+    threshold: Option<f64>,
+
+    #[clap(long, conflicts_with_all = &["day", "puzzle", "all", "format", "verify", "bench"])]
+    //Read sonar depths one line at a time from --input (which can be "-" for stdin), printing the running counts as they arrive
+    stream: bool,
+
+    #[clap(long, value_name = "N,...", value_delimiter = ',', requires = "stream")]
+    #[clap(parse(try_from_str))]
+    //Sizes of the sliding windows to count increases between while streaming [default: 3]
+    window_sizes: Vec<usize>,
+
+    #[clap(long, value_name = "SUM|MEAN|MIN|MAX|MEDIAN", requires = "stream")]
+    #[clap(parse(try_from_str))]
+    //How each sliding window is reduced to one number to compare with the next [default: sum]
+    window_stat: Option<WindowStat>,
+
+    #[clap(long, value_name = "DEPTH", requires = "stream")]
+    #[clap(parse(try_from_str))]
+    //Changes in depth smaller than this are treated as noise in the streaming summary [default: 0]
+    noise_tolerance: Option<u32>,
+
+    #[clap(short, long, value_name = "FILE", conflicts_with = "all")]
+    #[clap(parse(from_os_str))]
+    //Read the puzzle input from this file instead of the default one, or from stdin if it's "-"
+    input: Option<PathBuf>,
+
+    #[clap(long, value_name = "DIR", env = "ADVENT_INPUTS_DIR")]
+    #[clap(parse(from_os_str))]
+    //Directory containing the default input file for each puzzle [default: inputs in the working directory, or next to the executable]
+    inputs_dir: Option<PathBuf>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum PartSelection {
+    One,
+    Two,
+    Both,
+}
+
+impl PartSelection {
+    fn parts(&self) -> &'static [Part] {
+        match self {
+            PartSelection::One => &[Part::One],
+            PartSelection::Two => &[Part::Two],
+            PartSelection::Both => &[Part::One, Part::Two],
+        }
+    }
+}
+
+impl std::str::FromStr for PartSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(PartSelection::One),
+            "2" => Ok(PartSelection::Two),
+            both if "both".eq_ignore_ascii_case(both) => Ok(PartSelection::Both),
+            _ => Err(format!("Expected 1, 2 or both but got \"{}\"", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(args: &[&str]) -> Result<(), String> {
+        let args = Args::try_parse_from([&["advent_2021"], args].concat()).unwrap();
+        check_stream_options(&args)
+    }
+
+    #[test]
+    pub fn window_options_need_stream() {
+        assert_eq!(
+            Err(String::from(
+                "--window-sizes can only be used with --stream"
+            )),
+            check(&["-d", "1", "--window-sizes", "2"])
+        );
+        assert!(check(&["-p", "sonar", "--window-stat", "max"]).is_err());
+        assert!(check(&["-d", "1", "--noise-tolerance", "3"]).is_err());
+        assert_eq!(
+            Ok(()),
+            check(&[
+                "--stream",
+                "--window-sizes",
+                "2,3",
+                "--noise-tolerance",
+                "3"
+            ])
+        );
+        assert_eq!(Ok(()), check(&["-d", "1"]));
+    }
+
+    #[test]
+    pub fn inputs_dir_can_be_overridden() {
+        let args = Args::try_parse_from(["advent_2021", "--inputs-dir", "elsewhere"]).unwrap();
+        assert_eq!(Ok(PathBuf::from("elsewhere")), inputs_dir(&args));
+    }
+}
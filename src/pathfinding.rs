@@ -0,0 +1,253 @@
+use crate::array_grid::{ArrayGrid, Edges};
+use crate::regions::Connectivity;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// The best route found through a grid
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Path {
+    /// The total cost of every cell entered along the way, not counting the source cell
+    pub cost: u64,
+    /// Every cell on the route, from the source it started at to the target
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// The number of orthogonal steps between two cells, which never overestimates the cost of a path
+/// where every step costs at least 1. Useful as an A* heuristic.
+pub fn manhattan_distance(a: (usize, usize), b: (usize, usize)) -> u64 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u64
+}
+
+impl<T> ArrayGrid<T>
+where
+    T: Clone + Default,
+{
+    /// The route with the fewest steps from any of the `sources` to `target`, moving only between `passable` cells.
+    /// Returns None if the target can't be reached.
+    pub fn bfs(
+        &self,
+        sources: &[(usize, usize)],
+        target: (usize, usize),
+        connectivity: Connectivity,
+        passable: impl Fn(&T) -> bool,
+    ) -> Option<Path> {
+        let mut came_from: ArrayGrid<Option<(usize, usize)>> =
+            ArrayGrid::create(self.width(), self.height());
+        let mut visited: ArrayGrid<bool> = ArrayGrid::create(self.width(), self.height());
+        let mut queue = VecDeque::new();
+        for &(x, y) in sources {
+            if passable(self.get(x, y)) && !*visited.get(x, y) {
+                visited.set(x, y, true);
+                queue.push_back((x, y));
+            }
+        }
+
+        while let Some((x, y)) = queue.pop_front() {
+            if (x, y) == target {
+                let cells = reconstruct(&came_from, target);
+                return Some(Path {
+                    cost: cells.len() as u64 - 1,
+                    cells,
+                });
+            }
+
+            for ((nx, ny), val) in self.neighbors(x, y, connectivity.offsets(), Edges::Bounded) {
+                if !*visited.get(nx, ny) && passable(val) {
+                    visited.set(nx, ny, true);
+                    came_from.set(nx, ny, Some((x, y)));
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        None
+    }
+
+    /// The cheapest route from any of the `sources` to `target`, where entering a cell costs `cost(cell)`,
+    /// or it can't be entered at all if that's None.
+    /// Returns None if the target can't be reached.
+    pub fn dijkstra(
+        &self,
+        sources: &[(usize, usize)],
+        target: (usize, usize),
+        connectivity: Connectivity,
+        cost: impl Fn(&T) -> Option<u64>,
+    ) -> Option<Path> {
+        self.astar(sources, target, connectivity, cost, |_| 0)
+    }
+
+    /// Like `dijkstra()`, but guided towards the target by `heuristic`, which estimates the cost from a cell to
+    /// the target. It must never overestimate, or the route found might not be the cheapest; see `manhattan_distance()`.
+    pub fn astar(
+        &self,
+        sources: &[(usize, usize)],
+        target: (usize, usize),
+        connectivity: Connectivity,
+        cost: impl Fn(&T) -> Option<u64>,
+        heuristic: impl Fn((usize, usize)) -> u64,
+    ) -> Option<Path> {
+        let mut came_from: ArrayGrid<Option<(usize, usize)>> =
+            ArrayGrid::create(self.width(), self.height());
+        let mut best: ArrayGrid<Option<u64>> = ArrayGrid::create(self.width(), self.height());
+        let mut queue = BinaryHeap::new();
+        for &(x, y) in sources {
+            if cost(self.get(x, y)).is_some() {
+                best.set(x, y, Some(0));
+                queue.push(Reverse((heuristic((x, y)), 0, (x, y))));
+            }
+        }
+
+        while let Some(Reverse((_, so_far, (x, y)))) = queue.pop() {
+            if (x, y) == target {
+                return Some(Path {
+                    cost: so_far,
+                    cells: reconstruct(&came_from, target),
+                });
+            }
+            //There's already been a cheaper way here, so this entry is stale
+            if matches!(best.get(x, y), Some(b) if so_far > *b) {
+                continue;
+            }
+
+            for ((nx, ny), val) in self.neighbors(x, y, connectivity.offsets(), Edges::Bounded) {
+                let next = match cost(val) {
+                    Some(step) => so_far + step,
+                    None => continue,
+                };
+                if !matches!(best.get(nx, ny), Some(b) if next >= *b) {
+                    best.set(nx, ny, Some(next));
+                    came_from.set(nx, ny, Some((x, y)));
+                    queue.push(Reverse((next + heuristic((nx, ny)), next, (nx, ny))));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Follow the trail of where each cell was reached from back to a source, then flip it to run source first
+fn reconstruct(
+    came_from: &ArrayGrid<Option<(usize, usize)>>,
+    target: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let mut cells = vec![target];
+    while let Some(prev) = came_from.get(cells[cells.len() - 1].0, cells[cells.len() - 1].1) {
+        cells.push(*prev);
+    }
+    cells.reverse();
+    cells
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The risk levels from the day 15 example, where the cheapest route from the top left to the bottom right is 40
+    const RISKS: &str = "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
+    const MAZE: &str = "S.#.....
+.##.###.
+....#...
+###.#.#T";
+
+    #[test]
+    pub fn bfs_works() {
+        let maze = ArrayGrid::parse(MAZE, |c| c != '#').unwrap();
+        let path = maze
+            .bfs(&[(0, 0)], (7, 3), Connectivity::Four, |&open| open)
+            .unwrap();
+        assert_eq!(14, path.cost);
+        assert_eq!(15, path.cells.len());
+        assert_eq!((0, 0), path.cells[0]);
+        assert_eq!((7, 3), path.cells[14]);
+        for pair in path.cells.windows(2) {
+            assert_eq!(1, manhattan_distance(pair[0], pair[1]));
+            assert!(*maze.get(pair[1].0, pair[1].1));
+        }
+
+        let shortcut = maze
+            .bfs(&[(0, 0), (7, 0)], (7, 3), Connectivity::Four, |&open| open)
+            .unwrap();
+        assert_eq!(3, shortcut.cost);
+        assert_eq!((7, 0), shortcut.cells[0]);
+
+        let walled_in = ArrayGrid::parse(".#.", |c| c != '#').unwrap();
+        assert_eq!(
+            None,
+            walled_in.bfs(&[(0, 0)], (2, 0), Connectivity::Eight, |&open| open)
+        );
+    }
+
+    #[test]
+    pub fn dijkstra_works() {
+        let risks: ArrayGrid<u8> = ArrayGrid::parse_digits(RISKS).unwrap();
+        let path = risks
+            .dijkstra(&[(0, 0)], (9, 9), Connectivity::Four, |&r| Some(r as u64))
+            .unwrap();
+        assert_eq!(40, path.cost);
+
+        let total: u64 = path.cells[1..]
+            .iter()
+            .map(|&(x, y)| *risks.get(x, y) as u64)
+            .sum();
+        assert_eq!(40, total);
+
+        let same = risks
+            .dijkstra(&[(9, 9)], (9, 9), Connectivity::Four, |&r| Some(r as u64))
+            .unwrap();
+        assert_eq!(0, same.cost);
+        assert_eq!(vec![(9, 9)], same.cells);
+    }
+
+    #[test]
+    pub fn astar_works() {
+        let risks: ArrayGrid<u8> = ArrayGrid::parse_digits(RISKS).unwrap();
+        let target = (9, 9);
+        let path = risks
+            .astar(
+                &[(0, 0)],
+                target,
+                Connectivity::Four,
+                |&r| Some(r as u64),
+                |cell| manhattan_distance(cell, target),
+            )
+            .unwrap();
+        assert_eq!(40, path.cost);
+
+        //Cells with a risk of 9 can't be entered
+        let path = risks
+            .astar(
+                &[(0, 0)],
+                target,
+                Connectivity::Four,
+                |&r| if r == 9 { None } else { Some(r as u64) },
+                |cell| manhattan_distance(cell, target),
+            )
+            .unwrap();
+        assert!(path.cells.iter().all(|&(x, y)| *risks.get(x, y) != 9));
+    }
+
+    #[test]
+    pub fn impassable_sources_are_ignored() {
+        let risks: ArrayGrid<u8> = ArrayGrid::parse_digits(RISKS).unwrap();
+        let passable = |&r: &u8| if r == 9 { None } else { Some(r as u64) };
+        //(2, 3) has a risk of 9, so the route has to start from the top left
+        let path = risks
+            .dijkstra(&[(2, 3), (0, 0)], (3, 4), Connectivity::Four, passable)
+            .unwrap();
+        assert_eq!((0, 0), path.cells[0]);
+
+        assert_eq!(
+            None,
+            risks.dijkstra(&[(2, 3)], (3, 4), Connectivity::Four, passable)
+        );
+    }
+}
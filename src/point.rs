@@ -0,0 +1,317 @@
+use crate::array_grid::ArrayGrid;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// A position on a grid, or an offset between two positions. x increases to the right and y increases downwards.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    /// The number of orthogonal steps to `other`
+    pub fn manhattan_distance(&self, other: Point) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// The number of steps to `other` when diagonal steps are allowed too, like a king in chess
+    pub fn chebyshev_distance(&self, other: Point) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// The next point along in the given direction, eg `point.step(Direction::Up)`
+    pub fn step(&self, direction: impl Into<Point>) -> Point {
+        *self + direction.into()
+    }
+
+    /// The four points sharing an edge with this one, clockwise from the one above
+    pub fn orthogonal_neighbors(&self) -> impl Iterator<Item = Point> {
+        let point = *self;
+        Direction::ALL.iter().map(move |&d| point.step(d))
+    }
+
+    /// All eight points surrounding this one, including diagonals, clockwise from the one above
+    pub fn all_neighbors(&self) -> impl Iterator<Item = Point> {
+        let point = *self;
+        Compass::ALL.iter().map(move |&c| point.step(c))
+    }
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(fmt, "({}, {})", self.x, self.y)
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Point {
+        Point { x, y }
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Point {
+        Point {
+            x: x as i64,
+            y: y as i64,
+        }
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: i64) -> Point {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+/// One of the four orthogonal directions
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise from up, in the same order as `ORTHOGONAL`
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn turn_right(&self) -> Direction {
+        Direction::ALL[(*self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(&self) -> Direction {
+        Direction::ALL[(*self as usize + 3) % 4]
+    }
+
+    pub fn opposite(&self) -> Direction {
+        Direction::ALL[(*self as usize + 2) % 4]
+    }
+}
+
+impl From<Direction> for Point {
+    fn from(direction: Direction) -> Point {
+        match direction {
+            Direction::Up => Point::new(0, -1),
+            Direction::Right => Point::new(1, 0),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+        }
+    }
+}
+
+/// One of the eight compass directions, including diagonals. North is up.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Compass {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Compass {
+    /// Clockwise from north, in the same order as `SURROUNDING`
+    pub const ALL: [Compass; 8] = [
+        Compass::North,
+        Compass::NorthEast,
+        Compass::East,
+        Compass::SouthEast,
+        Compass::South,
+        Compass::SouthWest,
+        Compass::West,
+        Compass::NorthWest,
+    ];
+
+    pub fn opposite(&self) -> Compass {
+        Compass::ALL[(*self as usize + 4) % 8]
+    }
+}
+
+impl From<Compass> for Point {
+    fn from(compass: Compass) -> Point {
+        match compass {
+            Compass::North => Point::new(0, -1),
+            Compass::NorthEast => Point::new(1, -1),
+            Compass::East => Point::new(1, 0),
+            Compass::SouthEast => Point::new(1, 1),
+            Compass::South => Point::new(0, 1),
+            Compass::SouthWest => Point::new(-1, 1),
+            Compass::West => Point::new(-1, 0),
+            Compass::NorthWest => Point::new(-1, -1),
+        }
+    }
+}
+
+impl<T> ArrayGrid<T>
+where
+    T: Clone + Default,
+{
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= 0
+            && point.y >= 0
+            && (point.x as usize) < self.width()
+            && (point.y as usize) < self.height()
+    }
+
+    /// Every cell with its position, row by row
+    pub fn enumerate(&self) -> impl Iterator<Item = (Point, &T)> {
+        let width = self.width().max(1);
+        self.iter().enumerate().map(move |(idx, val)| {
+            let point = Point::new((idx % width) as i64, (idx / width) as i64);
+            (point, val)
+        })
+    }
+}
+
+/// `grid[point]` panics if the point is outside the grid; use `get_checked()` or `contains()` to check first
+impl<T> Index<Point> for ArrayGrid<T>
+where
+    T: Clone + Default,
+{
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        assert!(self.contains(point), "{} is outside the grid", point);
+        self.get(point.x as usize, point.y as usize)
+    }
+}
+
+impl<T> IndexMut<Point> for ArrayGrid<T>
+where
+    T: Clone + Default,
+{
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        assert!(self.contains(point), "{} is outside the grid", point);
+        self.get_mut(point.x as usize, point.y as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::array_grid::{ORTHOGONAL, SURROUNDING};
+
+    #[test]
+    pub fn arithmetic_works() {
+        let mut point = Point::new(3, -2);
+        assert_eq!(Point::new(4, 0), point + Point::new(1, 2));
+        assert_eq!(Point::new(2, -4), point - Point::new(1, 2));
+        assert_eq!(Point::new(-6, 4), -point * 2);
+
+        point += Point::new(1, 1);
+        assert_eq!(Point::new(4, -1), point);
+        point -= Point::new(4, -1);
+        assert_eq!(Point::ORIGIN, point);
+    }
+
+    #[test]
+    pub fn distances_work() {
+        let a = Point::new(1, 1);
+        let b = Point::new(-2, 5);
+        assert_eq!(7, a.manhattan_distance(b));
+        assert_eq!(4, a.chebyshev_distance(b));
+        assert_eq!(0, a.chebyshev_distance(a));
+    }
+
+    #[test]
+    pub fn directions_work() {
+        assert_eq!(Point::new(5, 4), Point::new(5, 5).step(Direction::Up));
+        assert_eq!(Point::new(6, 6), Point::new(5, 5).step(Compass::SouthEast));
+        assert_eq!(Direction::Left, Direction::Up.turn_left());
+        assert_eq!(Direction::Up, Direction::Left.turn_right());
+        assert_eq!(Direction::Down, Direction::Up.opposite());
+        assert_eq!(Compass::SouthWest, Compass::NorthEast.opposite());
+
+        let orthogonal: Vec<Point> = Direction::ALL.iter().map(|&d| d.into()).collect();
+        let expected: Vec<Point> = ORTHOGONAL.iter().map(|&o| o.into()).collect();
+        assert_eq!(expected, orthogonal);
+        let surrounding: Vec<Point> = Point::ORIGIN.all_neighbors().collect();
+        let expected: Vec<Point> = SURROUNDING.iter().map(|&o| o.into()).collect();
+        assert_eq!(expected, surrounding);
+    }
+
+    #[test]
+    pub fn index_works() {
+        let mut grid: ArrayGrid<u8> = ArrayGrid::create_from(3, 2, (0..6).collect());
+        assert_eq!(5, grid[Point::new(2, 1)]);
+        grid[Point::new(0, 1)] = 9;
+        assert_eq!(&9, grid.get(0, 1));
+        assert!(grid.contains(Point::new(2, 1)));
+        assert!(!grid.contains(Point::new(3, 1)));
+        assert!(!grid.contains(Point::new(0, -1)));
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn index_outside_panics() {
+        let grid: ArrayGrid<u8> = ArrayGrid::create(3, 2);
+        let _ = grid[Point::new(-1, 0)];
+    }
+
+    #[test]
+    pub fn enumerate_works() {
+        let grid: ArrayGrid<u8> = ArrayGrid::create_from(3, 2, (0..6).collect());
+        let cells: Vec<(Point, &u8)> = grid.enumerate().collect();
+        assert_eq!(6, cells.len());
+        assert_eq!((Point::new(0, 0), &0), cells[0]);
+        assert_eq!((Point::new(2, 0), &2), cells[2]);
+        assert_eq!((Point::new(1, 1), &4), cells[4]);
+        for (point, &val) in grid.enumerate() {
+            assert_eq!(val, grid[point]);
+        }
+    }
+}
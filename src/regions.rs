@@ -0,0 +1,176 @@
+use crate::array_grid::{ArrayGrid, Edges, ORTHOGONAL, SURROUNDING};
+use std::collections::VecDeque;
+
+/// Which neighbouring cells count as connected to each other
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Connectivity {
+    /// Only cells sharing an edge
+    Four,
+    /// Cells sharing an edge or a corner
+    Eight,
+}
+
+impl Connectivity {
+    pub fn offsets(&self) -> &'static [(i64, i64)] {
+        match self {
+            Connectivity::Four => ORTHOGONAL,
+            Connectivity::Eight => SURROUNDING,
+        }
+    }
+}
+
+/// The result of `ArrayGrid::label_components()`
+#[derive(Clone, Debug)]
+pub struct Components {
+    /// The component each cell belongs to, as an index into `sizes`, or None if the cell isn't passable
+    pub labels: ArrayGrid<Option<usize>>,
+    /// The number of cells in each component, in the order they were found (scanning row by row)
+    pub sizes: Vec<usize>,
+}
+
+impl Components {
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// The sizes of the `n` biggest components, biggest first
+    pub fn largest(&self, n: usize) -> Vec<usize> {
+        let mut sizes = self.sizes.clone();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes.truncate(n);
+        sizes
+    }
+}
+
+impl<T> ArrayGrid<T>
+where
+    T: Clone + Default,
+{
+    /// Every cell that can be reached from (x, y) by moving between `passable` cells, including (x, y) itself.
+    /// They're in the order they were reached, nearest first. If (x, y) isn't passable, there aren't any.
+    pub fn flood_fill(
+        &self,
+        x: usize,
+        y: usize,
+        connectivity: Connectivity,
+        passable: impl Fn(&T) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let mut visited: ArrayGrid<bool> = ArrayGrid::create(self.width(), self.height());
+        self.fill_from(x, y, connectivity, &passable, &mut visited)
+    }
+
+    /// Split the `passable` cells into groups which are connected to each other, eg the basins of a heightmap
+    pub fn label_components(
+        &self,
+        connectivity: Connectivity,
+        passable: impl Fn(&T) -> bool,
+    ) -> Components {
+        let mut visited: ArrayGrid<bool> = ArrayGrid::create(self.width(), self.height());
+        let mut labels: ArrayGrid<Option<usize>> = ArrayGrid::create(self.width(), self.height());
+        let mut sizes = vec![];
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if *visited.get(x, y) {
+                    continue;
+                }
+                let component = self.fill_from(x, y, connectivity, &passable, &mut visited);
+                if component.is_empty() {
+                    continue;
+                }
+
+                for &(cx, cy) in &component {
+                    labels.set(cx, cy, Some(sizes.len()));
+                }
+                sizes.push(component.len());
+            }
+        }
+
+        Components { labels, sizes }
+    }
+
+    /// Breadth-first search from (x, y), skipping anything already `visited` and marking everything it reaches
+    fn fill_from(
+        &self,
+        x: usize,
+        y: usize,
+        connectivity: Connectivity,
+        passable: &impl Fn(&T) -> bool,
+        visited: &mut ArrayGrid<bool>,
+    ) -> Vec<(usize, usize)> {
+        if *visited.get(x, y) || !passable(self.get(x, y)) {
+            return vec![];
+        }
+
+        let mut filled = vec![];
+        let mut queue = VecDeque::from(vec![(x, y)]);
+        visited.set(x, y, true);
+        while let Some((x, y)) = queue.pop_front() {
+            filled.push((x, y));
+            for ((nx, ny), val) in self.neighbors(x, y, connectivity.offsets(), Edges::Bounded) {
+                if !*visited.get(nx, ny) && passable(val) {
+                    visited.set(nx, ny, true);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        filled
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The heightmap from the day 9 example, where the basins are bounded by 9s
+    const HEIGHTMAP: &str = "2199943210
+3987894921
+9856789892
+8767896789
+9899965678";
+
+    #[test]
+    pub fn flood_fill_works() {
+        let grid: ArrayGrid<u8> = ArrayGrid::parse_digits(HEIGHTMAP).unwrap();
+        let basin = grid.flood_fill(0, 0, Connectivity::Four, |&h| h != 9);
+        assert_eq!(vec![(0, 0), (1, 0), (0, 1)], basin);
+
+        let basin = grid.flood_fill(9, 0, Connectivity::Four, |&h| h != 9);
+        assert_eq!(9, basin.len());
+        assert!(basin.contains(&(9, 2)));
+
+        assert!(grid
+            .flood_fill(2, 0, Connectivity::Four, |&h| h != 9)
+            .is_empty());
+    }
+
+    #[test]
+    pub fn label_components_works() {
+        let grid: ArrayGrid<u8> = ArrayGrid::parse_digits(HEIGHTMAP).unwrap();
+        let components = grid.label_components(Connectivity::Four, |&h| h != 9);
+        assert_eq!(vec![3, 9, 14, 9], components.sizes);
+        assert_eq!(vec![14, 9, 9], components.largest(3));
+        assert_eq!(&Some(0), components.labels.get(1, 0));
+        assert_eq!(&Some(2), components.labels.get(2, 2));
+        assert_eq!(&None, components.labels.get(2, 0));
+    }
+
+    #[test]
+    pub fn eight_connectivity_joins_diagonals() {
+        let grid = ArrayGrid::parse("#..\n.#.\n..#", |c| c == '#').unwrap();
+        let four = grid.label_components(Connectivity::Four, |&wall| wall);
+        let eight = grid.label_components(Connectivity::Eight, |&wall| wall);
+        assert_eq!(vec![1, 1, 1], four.sizes);
+        assert_eq!(vec![3], eight.sizes);
+        assert_eq!(
+            3,
+            grid.flood_fill(2, 2, Connectivity::Eight, |&wall| wall)
+                .len()
+        );
+        assert!(!eight.is_empty());
+    }
+}
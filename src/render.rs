@@ -0,0 +1,217 @@
+use crate::array_grid::ArrayGrid;
+use std::fmt::Display;
+use std::io::Write;
+
+/// Draws a grid as a block of text, with every cell padded to the same width so that the columns line up.
+/// Create one with `ArrayGrid::render()` or `ArrayGrid::render_with()`.
+pub struct TextRenderer<'a, T>
+where
+    T: Clone + Default,
+{
+    grid: &'a ArrayGrid<T>,
+    cell: Box<dyn Fn(&T) -> String + 'a>,
+    rulers: bool,
+}
+
+impl<'a, T> TextRenderer<'a, T>
+where
+    T: Clone + Default,
+{
+    /// Number each column along the top (vertically, one digit per line) and each row down the left side
+    pub fn with_rulers(mut self) -> TextRenderer<'a, T> {
+        self.rulers = true;
+        self
+    }
+}
+
+impl<T> std::fmt::Display for TextRenderer<'_, T>
+where
+    T: Clone + Default,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        let cells: Vec<String> = self.grid.iter().map(|c| (self.cell)(c)).collect();
+        let cell_width = cells
+            .iter()
+            .map(|c| c.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let separator = if cell_width > 1 { " " } else { "" };
+
+        let mut lines: Vec<String> = vec![];
+        let gutter_width = count_digits(self.grid.height().saturating_sub(1));
+        if self.rulers {
+            let ruler_height = count_digits(self.grid.width().saturating_sub(1));
+            for digit_idx in 0..ruler_height {
+                let digits = (0..self.grid.width()).map(|x| {
+                    let label = format!("{:>width$}", x, width = ruler_height);
+                    let digit = label.chars().nth(digit_idx).unwrap_or(' ');
+                    format!("{:>width$}", digit, width = cell_width)
+                });
+                lines.push(format!(
+                    "{:width$} {}",
+                    "",
+                    digits.collect::<Vec<_>>().join(separator),
+                    width = gutter_width
+                ));
+            }
+        }
+
+        for (y, row) in cells.chunks(self.grid.width().max(1)).enumerate() {
+            let row = row
+                .iter()
+                .map(|c| format!("{:>width$}", c, width = cell_width))
+                .collect::<Vec<_>>()
+                .join(separator);
+            if self.rulers {
+                lines.push(format!("{:>width$} {}", y, row, width = gutter_width));
+            } else {
+                lines.push(row);
+            }
+        }
+
+        write!(fmt, "{}", lines.join("\n"))
+    }
+}
+
+fn count_digits(n: usize) -> usize {
+    n.to_string().len()
+}
+
+impl<T> ArrayGrid<T>
+where
+    T: Clone + Default + Display,
+{
+    /// Render each cell using its `Display` impl, eg `println!("{}", grid.render().with_rulers())`
+    pub fn render(&self) -> TextRenderer<'_, T> {
+        self.render_with(|c| c.to_string())
+    }
+}
+
+impl<T> ArrayGrid<T>
+where
+    T: Clone + Default,
+{
+    /// Render each cell as whatever `cell` turns it into, eg `grid.render_with(|&wall| if wall { '#' } else { '.' })`
+    pub fn render_with<'a, S: Display>(
+        &'a self,
+        cell: impl Fn(&T) -> S + 'a,
+    ) -> TextRenderer<'a, T> {
+        TextRenderer {
+            grid: self,
+            cell: Box::new(move |c| cell(c).to_string()),
+            rulers: false,
+        }
+    }
+
+    /// Write the grid as a binary greyscale PGM image, one pixel per cell, with `shade` giving the
+    /// brightness of each from 0 (black) to 255 (white)
+    pub fn write_pgm(&self, out: &mut impl Write, shade: impl Fn(&T) -> u8) -> std::io::Result<()> {
+        write!(out, "P5\n{} {}\n255\n", self.width(), self.height())?;
+        let pixels: Vec<u8> = self.iter().map(shade).collect();
+        out.write_all(&pixels)
+    }
+
+    /// Write the grid as a binary colour PPM image, one pixel per cell, with `color` giving the colour of each.
+    /// See `Palette` for a simple way of picking the colours.
+    pub fn write_ppm(
+        &self,
+        out: &mut impl Write,
+        color: impl Fn(&T) -> Rgb,
+    ) -> std::io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width(), self.height())?;
+        let pixels: Vec<u8> = self.iter().flat_map(color).collect();
+        out.write_all(&pixels)
+    }
+}
+
+impl<T> std::fmt::Display for ArrayGrid<T>
+where
+    T: Clone + Default + Display,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        self.render().fmt(fmt)
+    }
+}
+
+/// Red, green and blue
+pub type Rgb = [u8; 3];
+
+/// Colours for small counts, eg the number of vents overlapping at a point.
+/// 0 gets the first colour, 1 the second and so on, with any larger numbers getting the last colour.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Palette {
+    colors: Vec<Rgb>,
+}
+
+impl Palette {
+    pub fn new(colors: Vec<Rgb>) -> Palette {
+        assert!(!colors.is_empty(), "A palette needs at least one colour");
+        Palette { colors }
+    }
+
+    /// Black, then getting hotter through red, orange and yellow to white
+    pub fn heat() -> Palette {
+        Palette::new(vec![
+            [0, 0, 0],
+            [128, 0, 0],
+            [255, 64, 0],
+            [255, 160, 0],
+            [255, 255, 0],
+            [255, 255, 255],
+        ])
+    }
+
+    pub fn color(&self, value: usize) -> Rgb {
+        self.colors[value.min(self.colors.len() - 1)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn display_works() {
+        let grid: ArrayGrid<u8> = ArrayGrid::parse_digits("123\n456").unwrap();
+        assert_eq!("123\n456", grid.to_string());
+
+        let grid = ArrayGrid::create_from(2, 2, vec![1u16, 200, 30, 4]);
+        assert_eq!("  1 200\n 30   4", grid.to_string());
+    }
+
+    #[test]
+    pub fn render_with_works() {
+        let grid = ArrayGrid::parse("#..\n.#.", |c| c == '#').unwrap();
+        let text = grid
+            .render_with(|&wall| if wall { '█' } else { ' ' })
+            .to_string();
+        assert_eq!("█  \n █ ", text);
+    }
+
+    #[test]
+    pub fn rulers_work() {
+        let grid: ArrayGrid<u8> = ArrayGrid::create(12, 2);
+        let expected = [
+            "            11",
+            "  012345678901",
+            "0 000000000000",
+            "1 000000000000",
+        ];
+        assert_eq!(expected.join("\n"), grid.render().with_rulers().to_string());
+    }
+
+    #[test]
+    pub fn images_work() {
+        let grid = ArrayGrid::create_from(2, 1, vec![0usize, 7]);
+
+        let mut pgm: Vec<u8> = vec![];
+        grid.write_pgm(&mut pgm, |&v| v as u8 * 10).unwrap();
+        assert_eq!(b"P5\n2 1\n255\n\x00\x46".to_vec(), pgm);
+
+        let mut ppm: Vec<u8> = vec![];
+        let palette = Palette::heat();
+        grid.write_ppm(&mut ppm, |&v| palette.color(v)).unwrap();
+        assert_eq!(b"P6\n2 1\n255\n\x00\x00\x00\xff\xff\xff".to_vec(), ppm);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::answer::Value;
+use crate::error::PuzzleResult;
+use crate::solver::{Part, Puzzle, PuzzleRun};
+use itertools::Itertools;
+use serde::Serialize;
+use std::time::Duration;
+
+/// How results are printed. `Text` is for people, the others are for feeding into other tools.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Expected text, json or csv but got \"{}\"", s)),
+        }
+    }
+}
+
+/// The result of one part of a puzzle, flattened out for the machine-readable formats.
+///
+/// This is a stable schema that dashboards depend on, so only ever add fields at the end.
+/// Times are in nanoseconds. When the input couldn't be read or parsed, every part gets a record
+/// with the error and no answer or times.
+#[derive(Serialize, PartialEq, Eq, Debug)]
+pub struct Record {
+    pub puzzle: &'static str,
+    pub day: usize,
+    pub part: u8,
+    /// The main value of the answer
+    pub answer: Option<Value>,
+    /// Every value of the answer, eg "power=198, gamma=22, epsilon=9"
+    pub details: Option<String>,
+    pub parse_ns: Option<u64>,
+    pub solve_ns: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// One record per part of each run
+pub fn records(runs: &[(&dyn Puzzle, PuzzleResult<PuzzleRun>)], parts: &[Part]) -> Vec<Record> {
+    let mut records = vec![];
+    for (puzzle, run) in runs {
+        match run {
+            Ok(run) => records.extend(run.parts.iter().map(|part_run| {
+                let answer = part_run.answer.as_ref();
+                Record {
+                    puzzle: puzzle.name(),
+                    day: puzzle.day(),
+                    part: part_run.part.number(),
+                    answer: answer.ok().map(|a| a.value().clone()),
+                    details: answer.ok().map(|a| a.to_string()),
+                    parse_ns: Some(nanos(run.parse_time)),
+                    solve_ns: Some(nanos(part_run.solve_time)),
+                    error: answer.err().map(|e| e.to_string()),
+                }
+            })),
+            Err(e) => records.extend(parts.iter().map(|part| Record {
+                puzzle: puzzle.name(),
+                day: puzzle.day(),
+                part: part.number(),
+                answer: None,
+                details: None,
+                parse_ns: None,
+                solve_ns: None,
+                error: Some(e.to_string()),
+            })),
+        }
+    }
+    records
+}
+
+/// Print the records as a JSON array
+pub fn print_json(records: &[Record]) {
+    let json = serde_json::to_string_pretty(records)
+        .expect("Records should always be serializable as JSON");
+    println!("{}", json);
+}
+
+/// Print the records as CSV, with a header row
+pub fn print_csv(records: &[Record]) {
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    for record in records {
+        writer
+            .serialize(record)
+            .expect("Couldn't write CSV to stdout");
+    }
+    writer.flush().expect("Couldn't write CSV to stdout");
+}
+
+/// Whole nanoseconds, saturating at u64::MAX
+pub(crate) fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// Print a table with a row for each puzzle showing the main value of its answers and how long it took, with the total time at the bottom
+pub fn print_summary(runs: &[(&dyn Puzzle, PuzzleResult<PuzzleRun>)], parts: &[Part]) {
+    let mut header = vec![String::from("Day"), String::from("Puzzle")];
+    header.extend(parts.iter().map(|part| format!("Part {}", part)));
+    header.push(String::from("Parse"));
+    header.push(String::from("Solve"));
+
+    let mut total_parse = Duration::ZERO;
+    let mut total_solve = Duration::ZERO;
+    let mut rows: Vec<Vec<String>> = Vec::with_capacity(runs.len());
+    for (puzzle, run) in runs {
+        let mut row = vec![puzzle.day().to_string(), puzzle.name().to_string()];
+        match run {
+            Ok(run) => {
+                for &part in parts {
+                    row.push(match run.get(part).map(|p| &p.answer) {
+                        Some(Ok(answer)) => answer.value().to_string(),
+                        Some(Err(_)) => String::from("ERROR"),
+                        None => String::from("-"),
+                    });
+                }
+                row.push(format_duration(run.parse_time));
+                row.push(format_duration(run.solve_time()));
+                total_parse += run.parse_time;
+                total_solve += run.solve_time();
+            }
+            Err(_) => {
+                row.extend(parts.iter().map(|_| String::from("ERROR")));
+                row.push(String::from("-"));
+                row.push(String::from("-"));
+            }
+        }
+        rows.push(row);
+    }
+
+    let mut total = vec![String::new(), String::from("Total")];
+    total.extend(parts.iter().map(|_| String::new()));
+    total.push(format_duration(total_parse));
+    total.push(format_duration(total_solve));
+
+    print_table(&header, &rows, Some(&total));
+}
+
+/// Print every error from the runs to stderr, so that they don't get lost in the summary table.
+/// Returns true if there weren't any.
+pub fn print_errors(runs: &[(&dyn Puzzle, PuzzleResult<PuzzleRun>)]) -> bool {
+    let errors: Vec<String> = runs
+        .iter()
+        .flat_map(|(_, run)| match run {
+            Ok(run) => run
+                .parts
+                .iter()
+                .filter_map(|p| p.answer.as_ref().err())
+                .map(|e| e.to_string())
+                .collect(),
+            Err(e) => vec![e.to_string()],
+        })
+        .collect();
+
+    for error in &errors {
+        eprintln!("{}", error);
+    }
+    errors.is_empty()
+}
+
+/// Print the rows with each column padded to line up, and the footer (if any) separated from the rest
+pub fn print_table(header: &[String], rows: &[Vec<String>], footer: Option<&[String]>) {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|col| {
+            std::iter::once(header)
+                .chain(rows.iter().map(|r| &r[..]))
+                .chain(footer)
+                .map(|r| r[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let format_row = |row: &[String]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let separator = widths.iter().map(|&width| "-".repeat(width)).join("  ");
+
+    println!("{}", format_row(header));
+    println!("{}", separator);
+    for row in rows {
+        println!("{}", format_row(row));
+    }
+    if let Some(footer) = footer {
+        println!("{}", separator);
+        println!("{}", format_row(footer));
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.2?}", duration)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::find_by_name;
+
+    #[test]
+    pub fn records_have_one_per_part() {
+        let whales = find_by_name("whales").unwrap();
+        let parts = [Part::One, Part::Two];
+        let runs = [
+            (whales, whales.run("16,1,2,0,4,2,7,1,2,14", &parts)),
+            (whales, whales.run("16,x", &parts)),
+        ];
+
+        let records = records(&runs, &parts);
+        assert_eq!(4, records.len());
+
+        assert_eq!(2, records[1].part);
+        assert_eq!(Some(Value::Number(168)), records[1].answer);
+        assert!(records[1]
+            .details
+            .as_ref()
+            .unwrap()
+            .starts_with("min_fuel=168,"));
+        assert!(records[1].solve_ns.is_some());
+        assert_eq!(None, records[1].error);
+
+        for record in &records[2..] {
+            assert_eq!(None, record.answer);
+            assert_eq!(None, record.parse_ns);
+            assert!(record.error.as_ref().unwrap().contains("line 1, column 4"));
+        }
+    }
+
+    #[test]
+    pub fn format_parses() {
+        assert_eq!(Ok(Format::Json), "JSON".parse());
+        assert_eq!(Ok(Format::Csv), "csv".parse());
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
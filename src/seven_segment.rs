@@ -1,59 +1,169 @@
-use itertools::Itertools;
+use crate::answer::Answer;
+use crate::error::{offset_within, LineError, PuzzleError, PuzzleResult};
+use crate::solver::Solver;
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
-pub fn run(input: String) -> Result<String, String> {
-    let io: Vec<(Vec<&str>, Vec<&str>)> = input
-        .lines()
-        .map(|line| {
-            let parts: Vec<&str> = line.split('|').map(|s| s.trim()).collect();
-            log::debug!("parts={:?}", parts);
-            let inputs: Vec<&str> = parts[0].split_whitespace().collect();
-            let outputs: Vec<&str> = parts[1].split_whitespace().collect();
-            (inputs, outputs)
-        })
-        .collect();
-
-    let part1_sum = io
-        .iter()
-        .map(|(_inputs, outputs)| {
-            outputs
-                .iter()
-                .filter(|&digit| matches!(digit.len(), 2 | 3 | 4 | 7))
-                .count()
-        })
-        .sum::<usize>();
-
-    let sum: usize = io
-        .iter()
-        .map(|(inputs, outputs)| {
-            let segment_map = infer_segment_map(inputs);
-            outputs
-                .iter()
-                .map(|raw_out| {
-                    let mut sorted_out: Vec<char> = raw_out.chars().collect();
-                    sorted_out.sort_unstable();
-                    String::from_iter(sorted_out.iter())
-                })
-                .map(|out| {
-                    if let Some(digit) = segment_map.get(&out) {
-                        digit.to_string()
-                    } else {
-                        panic!("Unable to map segment {}", out);
-                    }
-                })
-                .join("")
-                .parse::<usize>()
-                .unwrap()
-        })
-        .inspect(|num| {
+const NAME: &str = "seven-segment";
+const NUM_PATTERNS: usize = 10;
+const NUM_OUTPUTS: usize = 4;
+
+pub struct SevenSegment;
+
+impl Solver for SevenSegment {
+    type Input = Vec<Entry>;
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn day(&self) -> usize {
+        8
+    }
+
+    fn input_file(&self) -> &'static str {
+        "seven_segment.txt"
+    }
+
+    fn parse(&self, input: &str) -> PuzzleResult<Vec<Entry>> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(line_idx, line)| match split_entry(line) {
+                Ok((patterns, outputs)) => {
+                    //Every pattern was sliced out of the line by `split_entry()`
+                    let range = |part: &&str| {
+                        let start =
+                            offset_within(line, part).expect("Patterns are part of the line");
+                        start..start + part.len()
+                    };
+                    Ok(Entry {
+                        line_idx,
+                        line: line.to_string(),
+                        patterns: patterns.iter().map(range).collect(),
+                        outputs: outputs.iter().map(range).collect(),
+                    })
+                }
+                Err(e) => Err(PuzzleError::at_line(NAME, line_idx, e)),
+            })
+            .collect()
+    }
+
+    fn part1(&self, entries: &Vec<Entry>) -> PuzzleResult<Answer> {
+        let part1_sum = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .outputs()
+                    .filter(|&digit| matches!(digit.len(), 2 | 3 | 4 | 7))
+                    .count()
+            })
+            .sum::<usize>();
+
+        Ok(Answer::new("out_digits", part1_sum))
+    }
+
+    fn part2(&self, entries: &Vec<Entry>) -> PuzzleResult<Answer> {
+        let mut sum: usize = 0;
+        for entry in entries {
+            let num =
+                decode_output(entry).map_err(|e| PuzzleError::at_line(NAME, entry.line_idx, e))?;
             log::debug!("Out: {}", num);
-        })
-        .sum();
+            sum += num;
+        }
+
+        Ok(Answer::new("sum", sum))
+    }
+}
 
-    Ok(format!("part1_out_digits={}, part2_sum={}", part1_sum, sum))
+/// One line of the puzzle input, ie the ten unique signal patterns and the four output digits from a single display.
+/// The line is kept whole so that errors found while decoding it can point at the offending pattern,
+/// with each pattern and output stored as its position within the line.
+#[derive(Debug)]
+pub struct Entry {
+    line_idx: usize,
+    line: String,
+    patterns: Vec<Range<usize>>,
+    outputs: Vec<Range<usize>>,
 }
 
-fn infer_segment_map(inputs: &[&str]) -> HashMap<String, u8> {
+impl Entry {
+    fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(|r| &self.line[r.clone()])
+    }
+
+    fn outputs(&self) -> impl Iterator<Item = &str> {
+        self.outputs.iter().map(|r| &self.line[r.clone()])
+    }
+}
+
+/// Split a line into its signal patterns and output digits, checking that they're all valid
+fn split_entry(line: &str) -> Result<(Vec<&str>, Vec<&str>), LineError> {
+    let parts: Vec<&str> = line.split('|').collect();
+    log::debug!("parts={:?}", parts);
+    if let [raw_patterns, raw_outputs] = &parts[..] {
+        let patterns: Vec<&str> = raw_patterns.split_whitespace().collect();
+        let outputs: Vec<&str> = raw_outputs.split_whitespace().collect();
+
+        if patterns.len() != NUM_PATTERNS {
+            return Err(LineError::new(
+                line,
+                raw_patterns,
+                format!("expected {} signal patterns", NUM_PATTERNS),
+            ));
+        }
+        if outputs.len() != NUM_OUTPUTS {
+            return Err(LineError::new(
+                line,
+                raw_outputs,
+                format!("expected {} output digits", NUM_OUTPUTS),
+            ));
+        }
+        for pattern in patterns.iter().chain(outputs.iter()) {
+            if let Some((idx, c)) = pattern
+                .char_indices()
+                .find(|(_, c)| !('a'..='g').contains(c))
+            {
+                return Err(LineError::new(
+                    line,
+                    &pattern[idx..idx + c.len_utf8()],
+                    "segments must be named a through g",
+                ));
+            }
+        }
+
+        Ok((patterns, outputs))
+    } else {
+        Err(LineError::new(
+            line,
+            line,
+            "expected signal patterns and output digits separated by '|'",
+        ))
+    }
+}
+
+fn decode_output(entry: &Entry) -> Result<usize, LineError> {
+    let patterns: Vec<&str> = entry.patterns().collect();
+    let segment_map = infer_segment_map(&entry.line, &patterns)?;
+    entry.outputs().try_fold(0, |num, raw_out| {
+        match segment_map.get(&sort_segments(raw_out)) {
+            Some(&digit) => Ok(num * 10 + digit as usize),
+            None => Err(LineError::new(
+                &entry.line,
+                raw_out,
+                "output doesn't match any of the signal patterns",
+            )),
+        }
+    })
+}
+
+fn sort_segments(segments: &str) -> String {
+    let mut chars: Vec<char> = segments.chars().collect();
+    chars.sort_unstable();
+    String::from_iter(chars.iter())
+}
+
+fn infer_segment_map(line: &str, inputs: &[&str]) -> Result<HashMap<String, u8>, LineError> {
     /*
     2 segments => #1
     3 segments => #7
@@ -71,27 +181,26 @@ fn infer_segment_map(inputs: &[&str]) -> HashMap<String, u8> {
     2) Extract values for 2, 3, & 4 segments (they're just the first 3 values in the sorted result)
     3) For remaining segments, compute overlap with #1, #7 and #4 to determine values
     */
-    let mut inputs: Vec<String> = inputs
-        .iter()
-        .map(|s| {
-            let mut chars: Vec<char> = s.chars().collect();
-            chars.sort_unstable();
-            String::from_iter(chars.iter())
-        })
-        .collect();
-    inputs.sort_unstable_by_key(|a| a.len());
+    //Keep the original pattern around, so that errors can point at it
+    let mut inputs: Vec<(&str, String)> = inputs.iter().map(|&s| (s, sort_segments(s))).collect();
+    inputs.sort_unstable_by_key(|(_, sorted)| sorted.len());
 
     let mut results: Vec<SevenSegmentDigit> = Vec::with_capacity(10);
 
-    debug_assert!(inputs[0].len() == 2);
-    debug_assert!(inputs[1].len() == 3);
-    debug_assert!(inputs[2].len() == 4);
+    let lengths: Vec<usize> = inputs.iter().take(3).map(|(_, s)| s.len()).collect();
+    if lengths != [2, 3, 4] {
+        return Err(LineError::new(
+            line,
+            line,
+            "expected exactly one pattern each with 2, 3 and 4 segments",
+        ));
+    }
 
-    let num_1 = SevenSegmentDigit::new(inputs[0].clone(), 1);
-    let num_7 = SevenSegmentDigit::new(inputs[1].clone(), 7);
-    let num_4 = SevenSegmentDigit::new(inputs[2].clone(), 4);
+    let num_1 = SevenSegmentDigit::new(inputs[0].1.clone(), 1);
+    let num_7 = SevenSegmentDigit::new(inputs[1].1.clone(), 7);
+    let num_4 = SevenSegmentDigit::new(inputs[2].1.clone(), 4);
 
-    for segment_str in inputs.into_iter().skip(3) {
+    for (original, segment_str) in inputs.into_iter().skip(3) {
         let num_1_overlap = num_1.count_overlaps(&segment_str);
         let num_4_overlap = num_4.count_overlaps(&segment_str);
         let num_7_overlap = num_7.count_overlaps(&segment_str);
@@ -145,7 +254,13 @@ fn infer_segment_map(inputs: &[&str]) -> HashMap<String, u8> {
             {
                 SevenSegmentDigit::new(num_9, 9)
             }
-            _ => panic!("Unable to determine value for {}", segment_str),
+            _ => {
+                return Err(LineError::new(
+                    line,
+                    original,
+                    "unable to determine which digit this is",
+                ))
+            }
         };
 
         results.push(digit);
@@ -159,7 +274,7 @@ fn infer_segment_map(inputs: &[&str]) -> HashMap<String, u8> {
     results.into_iter().for_each(|d7| {
         map.insert(d7.key, d7.digit);
     });
-    map
+    Ok(map)
 }
 
 struct SevenSegmentDigit {
@@ -182,3 +297,64 @@ impl SevenSegmentDigit {
         other.chars().filter(|c| self.segments.contains(c)).count()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE: &str =
+        "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
+
+    #[test]
+    pub fn sample_works() {
+        let entries = SevenSegment.parse(SAMPLE).unwrap();
+        assert_eq!(
+            Ok(Answer::new("out_digits", 26u32)),
+            SevenSegment.part1(&entries)
+        );
+        assert_eq!(
+            Ok(Answer::new("sum", 61229u32)),
+            SevenSegment.part2(&entries)
+        );
+    }
+
+    #[test]
+    pub fn bad_segment_reports_location() {
+        let input = "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgx gcbe";
+        match SevenSegment.parse(input) {
+            Err(PuzzleError::Parse {
+                line, column, text, ..
+            }) => {
+                assert_eq!(1, line);
+                assert_eq!(81, column);
+                assert_eq!("x", text);
+            }
+            other => panic!("Expected a parse error but got {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn undecodable_output_is_an_error() {
+        let input = "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd bcde";
+        let entries = SevenSegment.parse(input).unwrap();
+        match SevenSegment.part2(&entries) {
+            Err(PuzzleError::Parse {
+                line, column, text, ..
+            }) => {
+                assert_eq!(1, line);
+                assert_eq!(83, column);
+                assert_eq!("bcde", text);
+            }
+            other => panic!("bcde isn't one of the patterns, but got {:?}", other),
+        }
+    }
+}
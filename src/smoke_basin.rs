@@ -1,30 +1,85 @@
+use crate::answer::Answer;
 use crate::array_grid::ArrayGrid;
+use crate::error::{PuzzleError, PuzzleResult};
+use crate::regions::Connectivity;
+use crate::render::Rgb;
+use crate::solver::Solver;
 use itertools::Itertools;
 use log::debug;
+use std::collections::BTreeMap;
+use std::io::Write;
 
-pub fn run(input: String) -> Result<String, String> {
-    let width: i64 = input.lines().next().ok_or("No lines")?.len() as i64;
-    let height: i64 = input.lines().count() as i64;
-    let input: Vec<u8> = input
-        .lines()
-        .flat_map(|line| line.chars().map(|c| c.to_string().parse::<u8>().unwrap()))
-        .collect();
-    let grid: ArrayGrid<u8> = ArrayGrid::create_from(width as usize, height as usize, input);
-
-    let mut local_minima: Vec<u8> = vec![];
-
-    for y in 0..height {
-        for x in 0..width {
-            let val = grid.get(x as usize, y as usize);
-            let neighbors: Vec<&u8> = (vec![
-                grid.get_checked(x - 1, y),
-                grid.get_checked(x, y - 1),
-                grid.get_checked(x + 1, y),
-                grid.get_checked(x, y + 1),
-            ] as Vec<Option<&u8>>)
-                .into_iter()
-                .filter_map(|x| x)
-                .collect();
+const NAME: &str = "smoke-basin";
+
+pub struct SmokeBasin;
+
+impl Solver for SmokeBasin {
+    type Input = ArrayGrid<u8>;
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn day(&self) -> usize {
+        9
+    }
+
+    fn input_file(&self) -> &'static str {
+        "smoke_basin.txt"
+    }
+
+    fn parse(&self, input: &str) -> PuzzleResult<ArrayGrid<u8>> {
+        parse_heightmap(input)
+    }
+
+    fn part1(&self, grid: &ArrayGrid<u8>) -> PuzzleResult<Answer> {
+        find_risk(grid)
+    }
+
+    fn part2(&self, grid: &ArrayGrid<u8>) -> PuzzleResult<Answer> {
+        find_largest_basins(grid)
+    }
+}
+
+fn parse_heightmap(input: &str) -> PuzzleResult<ArrayGrid<u8>> {
+    ArrayGrid::parse_digits(input).map_err(|e| e.into_puzzle_error(NAME))
+}
+
+fn find_risk(grid: &ArrayGrid<u8>) -> PuzzleResult<Answer> {
+    let risk: u32 = low_points(grid)
+        .iter()
+        .map(|&(x, y)| *grid.get(x, y) as u32 + 1)
+        .sum();
+
+    Ok(Answer::new("risk", risk))
+}
+
+fn find_largest_basins(grid: &ArrayGrid<u8>) -> PuzzleResult<Answer> {
+    let mut sizes = basin_sizes(grid);
+    let num_basins = sizes.len();
+    if num_basins < 3 {
+        return Err(PuzzleError::unsolvable(
+            NAME,
+            format!("expected at least 3 basins but found {}", num_basins),
+        ));
+    }
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.truncate(3);
+    let product: usize = sizes.iter().product();
+
+    Ok(Answer::new("product", product)
+        .with("basins", num_basins)
+        .with("largest", sizes.iter().join(",")))
+}
+
+/// The positions of every cell which is lower than all of its neighbours
+pub fn low_points(grid: &ArrayGrid<u8>) -> Vec<(usize, usize)> {
+    let mut local_minima = vec![];
+
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let val = grid.get(x, y);
+            let neighbors: Vec<&u8> = grid.orthogonal_neighbors(x, y).map(|(_, n)| n).collect();
             let has_lower_neighbor = neighbors.iter().any(|&x| x <= val);
 
             debug!(
@@ -36,7 +91,7 @@ pub fn run(input: String) -> Result<String, String> {
                 has_lower_neighbor
             );
             if !has_lower_neighbor {
-                local_minima.push(*val);
+                local_minima.push((x, y));
             }
         }
     }
@@ -44,9 +99,236 @@ pub fn run(input: String) -> Result<String, String> {
     debug!(
         "num_minima={}, points={}",
         local_minima.len(),
-        local_minima.iter().join(",")
+        local_minima
+            .iter()
+            .map(|(x, y)| format!("({}, {})", x, y))
+            .join(",")
     );
-    let risk: u32 = local_minima.iter().map(|&h| h as u32 + 1).sum();
+    local_minima
+}
+
+/// The size of the basin around each low point, in the same order as `low_points()`.
+/// A basin is everything that flows down to its low point, which stops at cells of height 9.
+pub fn basin_sizes(grid: &ArrayGrid<u8>) -> Vec<usize> {
+    low_points(grid)
+        .into_iter()
+        .map(|(x, y)| grid.flood_fill(x, y, Connectivity::Four, |&h| h != 9).len())
+        .collect()
+}
 
-    Ok(format!("risk={}", risk))
+/// One basin in a `BasinMap`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Basin {
+    /// The lowest cell in the basin. If several share the lowest height, it's the first of them going row by row.
+    pub low_point: (usize, usize),
+    pub size: usize,
+    /// How far the basin drops from its highest cell down to the low point
+    pub depth: u8,
+}
+
+/// The whole heightmap split up into basins, for sanity checking the basin logic against real inputs
+#[derive(Clone, Debug)]
+pub struct BasinMap {
+    /// The basin each cell belongs to, as an index into `basins`, or None for the ridges of 9s between them
+    pub labels: ArrayGrid<Option<usize>>,
+    /// In the order they were found, scanning row by row
+    pub basins: Vec<Basin>,
+}
+
+/// Colours for neighbouring basins to cycle through, so they stand out from each other
+const BASIN_COLORS: &[Rgb] = &[
+    [230, 25, 75],
+    [60, 180, 75],
+    [255, 225, 25],
+    [0, 130, 200],
+    [245, 130, 48],
+    [145, 30, 180],
+    [70, 240, 240],
+    [240, 50, 230],
+];
+
+/// Letters for neighbouring basins to cycle through in `BasinMap::render()`
+const BASIN_SYMBOLS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Find every basin in the heightmap, which unlike `basin_sizes()` doesn't assume each one has a single low point
+pub fn basin_map(grid: &ArrayGrid<u8>) -> BasinMap {
+    let components = grid.label_components(Connectivity::Four, |&h| h != 9);
+
+    let mut lowest: Vec<Option<(u8, (usize, usize))>> = vec![None; components.len()];
+    let mut highest: Vec<u8> = vec![0; components.len()];
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            if let Some(id) = *components.labels.get(x, y) {
+                let height = *grid.get(x, y);
+                if !matches!(lowest[id], Some((low, _)) if low <= height) {
+                    lowest[id] = Some((height, (x, y)));
+                }
+                highest[id] = highest[id].max(height);
+            }
+        }
+    }
+
+    let basins = components
+        .sizes
+        .iter()
+        .zip(lowest.into_iter().zip(highest))
+        .map(|(&size, (lowest, highest))| {
+            let (low, low_point) = lowest.expect("Every basin has at least one cell");
+            Basin {
+                low_point,
+                size,
+                depth: highest - low,
+            }
+        })
+        .collect();
+
+    BasinMap {
+        labels: components.labels,
+        basins,
+    }
+}
+
+impl BasinMap {
+    /// How many basins there are of each size, smallest first
+    pub fn size_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for basin in &self.basins {
+            *histogram.entry(basin.size).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Draw each basin as a letter, with the ridges between them as #
+    pub fn render(&self) -> String {
+        self.labels
+            .render_with(|label| match label {
+                Some(id) => BASIN_SYMBOLS[id % BASIN_SYMBOLS.len()] as char,
+                None => '#',
+            })
+            .to_string()
+    }
+
+    /// Write the heightmap as a PPM image with each basin in its own colour, getting darker further up
+    /// the sides, and the ridges in black
+    pub fn write_ppm(&self, heights: &ArrayGrid<u8>, out: &mut impl Write) -> std::io::Result<()> {
+        let colors = self.labels.zip_with(heights, |label, &height| match label {
+            Some(id) => {
+                let [r, g, b] = BASIN_COLORS[id % BASIN_COLORS.len()];
+                let shade = |c: u8| (c as u16 * (18 - height.min(9) as u16) / 18) as u8;
+                [shade(r), shade(g), shade(b)]
+            }
+            None => [0, 0, 0],
+        });
+        colors.write_ppm(out, |&color| color)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE: &str = "2199943210
+3987894921
+9856789892
+8767896789
+9899965678";
+
+    #[test]
+    pub fn sample_part1() {
+        let grid = SmokeBasin.parse(SAMPLE).unwrap();
+        assert_eq!(Ok(Answer::new("risk", 15u32)), SmokeBasin.part1(&grid));
+    }
+
+    #[test]
+    pub fn sample_low_points() {
+        let grid = SmokeBasin.parse(SAMPLE).unwrap();
+        assert_eq!(vec![(1, 0), (9, 0), (2, 2), (6, 4)], low_points(&grid));
+        assert_eq!(vec![3, 9, 14, 9], basin_sizes(&grid));
+    }
+
+    #[test]
+    pub fn sample_basin_map() {
+        let grid = SmokeBasin.parse(SAMPLE).unwrap();
+        let map = basin_map(&grid);
+        assert_eq!(4, map.basins.len());
+        assert_eq!(
+            Basin {
+                low_point: (1, 0),
+                size: 3,
+                depth: 2
+            },
+            map.basins[0]
+        );
+        assert_eq!(
+            Basin {
+                low_point: (9, 0),
+                size: 9,
+                depth: 4
+            },
+            map.basins[1]
+        );
+        let basin_lows: Vec<(usize, usize)> = map.basins.iter().map(|b| b.low_point).collect();
+        assert_eq!(low_points(&grid), basin_lows);
+
+        let histogram: Vec<(usize, usize)> = map.size_histogram().into_iter().collect();
+        assert_eq!(vec![(3, 1), (9, 2), (14, 1)], histogram);
+
+        let rendered = map.render();
+        let rows: Vec<&str> = rendered.lines().collect();
+        assert_eq!("aa###bbbbb", rows[0]);
+        assert_eq!("a#ccc#b#bb", rows[1]);
+    }
+
+    #[test]
+    pub fn basin_map_image() {
+        let grid = SmokeBasin.parse(SAMPLE).unwrap();
+        let mut ppm: Vec<u8> = vec![];
+        basin_map(&grid).write_ppm(&grid, &mut ppm).unwrap();
+
+        let header = b"P6\n10 5\n255\n";
+        assert_eq!(header.len() + 10 * 5 * 3, ppm.len());
+        let pixel = |x: usize, y: usize| {
+            let start = header.len() + (x + y * 10) * 3;
+            ppm[start..start + 3].to_vec()
+        };
+        assert_eq!(vec![0, 0, 0], pixel(2, 0));
+        //The top right basin's low point is at full brightness
+        assert_eq!(BASIN_COLORS[1].to_vec(), pixel(9, 0));
+    }
+
+    #[test]
+    pub fn sample_part2() {
+        let grid = SmokeBasin.parse(SAMPLE).unwrap();
+        let expected = Answer::new("product", 1134u32)
+            .with("basins", 4u32)
+            .with("largest", "14,9,9");
+        assert_eq!(Ok(expected), SmokeBasin.part2(&grid));
+    }
+
+    #[test]
+    pub fn part2_needs_three_basins() {
+        let grid = SmokeBasin.parse("1239\n2349\n9993").unwrap();
+        assert_eq!(2, basin_sizes(&grid).len());
+        assert!(matches!(
+            SmokeBasin.part2(&grid),
+            Err(PuzzleError::Unsolvable { .. })
+        ));
+    }
+
+    #[test]
+    pub fn parse_heightmap_errors() {
+        match parse_heightmap("123\n4a6") {
+            Err(PuzzleError::Parse {
+                line, column, text, ..
+            }) => {
+                assert_eq!(2, line);
+                assert_eq!(2, column);
+                assert_eq!("a", text);
+            }
+            other => panic!("Expected a parse error but got {:?}", other),
+        }
+
+        parse_heightmap("123\n45").expect_err("Rows must all be the same width");
+        parse_heightmap("").expect_err("The heightmap can't be empty");
+    }
 }
@@ -0,0 +1,210 @@
+use crate::answer::Answer;
+use crate::error::PuzzleResult;
+use crate::{
+    binary, dive, hydrothermal, lanternfish, seven_segment, smoke_basin, sonar, squid, whales,
+};
+use std::time::{Duration, Instant};
+
+/// A solution to a single day of the advent calendar.
+///
+/// Each puzzle module provides a unit struct implementing this, and registers it in `PUZZLES`.
+/// Parsing is kept separate from solving so that the two can be timed independently.
+pub trait Solver {
+    /// The puzzle input, after parsing
+    type Input;
+
+    /// Name used to select the puzzle from the commandline, eg "smoke-basin"
+    fn name(&self) -> &'static str;
+
+    /// Day of the advent calendar the puzzle was released (starting at 1)
+    fn day(&self) -> usize;
+
+    /// File name of the puzzle input, relative to the inputs directory
+    fn input_file(&self) -> &'static str;
+
+    fn parse(&self, input: &str) -> PuzzleResult<Self::Input>;
+
+    fn part1(&self, input: &Self::Input) -> PuzzleResult<Answer>;
+
+    fn part2(&self, input: &Self::Input) -> PuzzleResult<Answer>;
+
+    fn solve(&self, part: Part, input: &Self::Input) -> PuzzleResult<Answer> {
+        match part {
+            Part::One => self.part1(input),
+            Part::Two => self.part2(input),
+        }
+    }
+}
+
+/// A `Solver` with its parsed input type hidden, so that they can all be kept in one list.
+/// There's no need to implement this directly, every `Solver` gets it for free.
+pub trait Puzzle {
+    fn name(&self) -> &'static str;
+
+    fn day(&self) -> usize;
+
+    fn input_file(&self) -> &'static str;
+
+    /// Parse the input once, then solve each of the given parts using it
+    fn run(&self, input: &str, parts: &[Part]) -> PuzzleResult<PuzzleRun>;
+}
+
+impl<S: Solver> Puzzle for S {
+    fn name(&self) -> &'static str {
+        Solver::name(self)
+    }
+
+    fn day(&self) -> usize {
+        Solver::day(self)
+    }
+
+    fn input_file(&self) -> &'static str {
+        Solver::input_file(self)
+    }
+
+    fn run(&self, input: &str, parts: &[Part]) -> PuzzleResult<PuzzleRun> {
+        let start = Instant::now();
+        let parsed = self.parse(input)?;
+        let parse_time = start.elapsed();
+
+        let parts = parts
+            .iter()
+            .map(|&part| {
+                let start = Instant::now();
+                let answer = self.solve(part, &parsed);
+                PartRun {
+                    part,
+                    answer,
+                    solve_time: start.elapsed(),
+                }
+            })
+            .collect();
+
+        Ok(PuzzleRun { parse_time, parts })
+    }
+}
+
+/// The answers from running a puzzle, and how long it took
+#[derive(Debug)]
+pub struct PuzzleRun {
+    pub parse_time: Duration,
+    pub parts: Vec<PartRun>,
+}
+
+impl PuzzleRun {
+    /// Total time spent solving, across all of the parts
+    pub fn solve_time(&self) -> Duration {
+        self.parts.iter().map(|p| p.solve_time).sum()
+    }
+
+    pub fn get(&self, part: Part) -> Option<&PartRun> {
+        self.parts.iter().find(|p| p.part == part)
+    }
+}
+
+#[derive(Debug)]
+pub struct PartRun {
+    pub part: Part,
+    pub answer: PuzzleResult<Answer>,
+    pub solve_time: Duration,
+}
+
+/// Each day's puzzle comes in two parts, the second unlocked by solving the first
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for Part {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(fmt, "{}", self.number())
+    }
+}
+
+/// Every puzzle solved so far, in calendar order.
+/// Adding a new day only requires appending its solver here.
+pub const PUZZLES: &[&dyn Puzzle] = &[
+    &sonar::Sonar,
+    &dive::Dive,
+    &binary::Binary,
+    &squid::Squid,
+    &hydrothermal::Hydrothermal,
+    &lanternfish::Lanternfish,
+    &whales::Whales,
+    &seven_segment::SevenSegment,
+    &smoke_basin::SmokeBasin,
+];
+
+pub fn find_by_day(day: usize) -> Option<&'static dyn Puzzle> {
+    PUZZLES.iter().copied().find(|p| p.day() == day)
+}
+
+pub fn find_by_name(name: &str) -> Option<&'static dyn Puzzle> {
+    if "latest".eq_ignore_ascii_case(name) {
+        return Some(latest());
+    }
+    PUZZLES
+        .iter()
+        .copied()
+        .find(|p| p.name().eq_ignore_ascii_case(name))
+}
+
+pub fn latest() -> &'static dyn Puzzle {
+    *PUZZLES
+        .last()
+        .expect("Puzzles list was unexpectedly empty, bad programmer!")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::answer::Value;
+    use itertools::Itertools;
+
+    #[test]
+    pub fn puzzles_are_in_calendar_order() {
+        for (idx, puzzle) in PUZZLES.iter().enumerate() {
+            assert_eq!(idx + 1, puzzle.day(), "{} is out of order", puzzle.name());
+        }
+    }
+
+    #[test]
+    pub fn puzzle_names_are_unique() {
+        assert!(PUZZLES.iter().map(|p| p.name()).all_unique());
+    }
+
+    #[test]
+    pub fn run_times_each_part() {
+        let run = find_by_name("whales")
+            .unwrap()
+            .run("16,1,2,0,4,2,7,1,2,14", &[Part::Two])
+            .unwrap();
+        assert_eq!(1, run.parts.len());
+        assert_eq!(Part::Two, run.parts[0].part);
+        let answer = run.parts[0].answer.as_ref().unwrap();
+        assert_eq!(
+            Some(("min_fuel", &Value::Number(168))),
+            answer.fields().next()
+        );
+        assert!(run.get(Part::One).is_none());
+    }
+
+    #[test]
+    pub fn lookups_work() {
+        assert_eq!("squid", find_by_day(4).unwrap().name());
+        assert_eq!(7, find_by_name("whales").unwrap().day());
+        assert_eq!(PUZZLES.len(), find_by_name("latest").unwrap().day());
+        assert!(find_by_day(0).is_none());
+        assert!(find_by_name("no-such-puzzle").is_none());
+    }
+}
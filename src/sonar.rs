@@ -1,41 +1,67 @@
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_number, PuzzleError, PuzzleResult};
+use crate::solver::Solver;
 use sliding_windows::{IterExt, Storage};
 use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::str::FromStr;
 
-pub fn run(input: String) -> Result<String, String> {
-    let depths = input.lines().map(|line| match line.parse() {
-        Err(why) => panic!("Failed to parse {} to integer because {}", line, why),
-        Ok(num) => num,
-    });
+const NAME: &str = "sonar";
 
-    //For educational reasons I wrote this to avoid cloning or re-reading the entire list of depths, even though in this exact case it would not be problematic.
-    //This led to the following somewhat-awkward cloning of iterators. Problem is that I want `calculate_direction()` to take either a plain iterator of depths,
-    //or an iterator of the sliding windows produced from `calculate_sliding_window_sums()`. But the former would be iterating references, while the latter iterates owned values.
-    //This is the best solution I could find that doesn't re-read the depths or re-allocate an equal (or almost-equal) amount of space.
-    let mut depths_iter_1 = depths;
-    let mut depths_iter_2 = depths_iter_1.clone();
+/// The number of depths summed together in each sliding window for part 2
+pub const WINDOW_SIZE: usize = 3;
 
-    let mut depth_diffs = calculate_direction(&mut depths_iter_1);
-    let count = calculate_increase_count(&mut depth_diffs);
-    println!("Number of increases: {}", count);
+pub struct Sonar;
 
-    let mut storage = Storage::new(3);
-    let mut windows = calculate_sliding_window_sums(&mut depths_iter_2, &mut storage);
-    let mut window_diffs = calculate_direction(&mut windows);
-    let count = calculate_increase_count(&mut window_diffs);
-    println!("Number of window increases: {}", count);
+impl Solver for Sonar {
+    type Input = Vec<u32>;
 
-    Ok(String::from("Ran successfully"))
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn day(&self) -> usize {
+        1
+    }
+
+    fn input_file(&self) -> &'static str {
+        "sonar.txt"
+    }
+
+    fn parse(&self, input: &str) -> PuzzleResult<Vec<u32>> {
+        parse_lines(NAME, input, |line| parse_number(line, line.trim())).collect()
+    }
+
+    fn part1(&self, depths: &Vec<u32>) -> PuzzleResult<Answer> {
+        let mut depths = depths.iter().copied();
+        let mut depth_diffs = calculate_direction(&mut depths);
+        let count = calculate_increase_count(&mut depth_diffs);
+        Ok(Answer::new("increases", count))
+    }
+
+    fn part2(&self, depths: &Vec<u32>) -> PuzzleResult<Answer> {
+        //For educational reasons I wrote this to avoid cloning the entire list of depths, even though in this exact case it would not be problematic.
+        //The awkward part is that I want `calculate_direction()` to take either a plain iterator of depths,
+        //or an iterator of the sliding windows produced from `calculate_sliding_windows()`. But the former would be iterating references, while the latter iterates owned values.
+        let mut depths = depths.iter().copied();
+        let mut storage = Storage::new(WINDOW_SIZE);
+        let mut windows = calculate_sliding_windows(&mut depths, &mut storage, WindowStat::Sum);
+        let mut window_diffs = calculate_direction(&mut windows);
+        let count = calculate_increase_count(&mut window_diffs);
+        Ok(Answer::new("window_increases", count))
+    }
 }
 
-/// Calculate the direction between successive depth measurements in the given stream
+/// Calculate the direction between successive depth measurements (or window statistics) in the given stream
 /// TODO: This should probably return Option<Iterator> instead, to account for error cases (eg, 0 or 1 element in the iterator)
-fn calculate_direction(
-    depths: &mut dyn Iterator<Item = u16>,
+fn calculate_direction<T: PartialOrd + Copy>(
+    depths: &mut dyn Iterator<Item = T>,
 ) -> impl Iterator<Item = DepthDirection> + '_ {
-    let mut prev: Option<u16> = None;
+    let mut prev: Option<T> = None;
 
     depths
-        .map(move |d: u16| {
+        .map(move |d: T| {
             let direction = prev.map(|p| {
                 let diff = d.partial_cmp(&p).expect("How is your depth NaN?");
                 match diff {
@@ -51,38 +77,695 @@ fn calculate_direction(
 }
 
 /// Calculate the number of times the given iterator contains DepthDirection::Up
-fn calculate_increase_count(directions: &mut impl Iterator<Item = DepthDirection>) -> u16 {
+fn calculate_increase_count(directions: &mut impl Iterator<Item = DepthDirection>) -> u64 {
     directions
         .filter(|c: &DepthDirection| *c == DepthDirection::Up)
-        .count() as u16
+        .count() as u64
 }
 
-/// Calculates the sum of the values in the stream on a sliding window.
+/// Reduces the values in the stream on a sliding window to one statistic each, eg their sum.
 /// The size of the sliding window is determined by the `Storage<>` passed in.
 /// TODO: This should probably return `Option<Iterator<_>>` for error cases (eg, less than one window of data)
-fn calculate_sliding_window_sums<'a>(
-    depths: &'a mut impl Iterator<Item = u16>,
-    storage: &'a mut Storage<u16>,
-) -> impl Iterator<Item = u16> + 'a {
+fn calculate_sliding_windows<'a>(
+    depths: &'a mut impl Iterator<Item = u32>,
+    storage: &'a mut Storage<u32>,
+    stat: WindowStat,
+) -> impl Iterator<Item = f64> + 'a {
     depths
         .sliding_windows(storage)
-        .map(|window| window.iter().copied().sum())
+        .map(move |window| stat.apply(window.iter().copied()))
+}
+
+/// How a sliding window of depths is boiled down to one number, to compare with the next window
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WindowStat {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Median,
+}
+
+impl WindowStat {
+    /// Sums are accumulated as u64, so they can't overflow however deep the depths get.
+    /// Panics if the window is empty.
+    pub fn apply(&self, window: impl Iterator<Item = u32>) -> f64 {
+        let mut window = window.map(u64::from).peekable();
+        assert!(window.peek().is_some(), "A window needs at least one depth");
+        match self {
+            WindowStat::Sum => window.sum::<u64>() as f64,
+            WindowStat::Mean => {
+                let (sum, len) = window.fold((0, 0), |(sum, len), depth| (sum + depth, len + 1));
+                sum as f64 / len as f64
+            }
+            WindowStat::Min => window.min().unwrap() as f64,
+            WindowStat::Max => window.max().unwrap() as f64,
+            WindowStat::Median => {
+                let mut depths: Vec<u64> = window.collect();
+                depths.sort_unstable();
+                let mid = depths.len() / 2;
+                if depths.len() % 2 == 1 {
+                    depths[mid] as f64
+                } else {
+                    (depths[mid - 1] + depths[mid]) as f64 / 2.0
+                }
+            }
+        }
+    }
 }
 
-#[derive(PartialEq, Debug)]
-enum DepthDirection {
+impl FromStr for WindowStat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sum" => Ok(WindowStat::Sum),
+            "mean" => Ok(WindowStat::Mean),
+            "min" => Ok(WindowStat::Min),
+            "max" => Ok(WindowStat::Max),
+            "median" => Ok(WindowStat::Median),
+            _ => Err(format!(
+                "Expected sum, mean, min, max or median but got \"{}\"",
+                s
+            )),
+        }
+    }
+}
+
+/// Running totals of how often the depth increased, updated one depth at a time.
+/// It counts increases between single depths, and between sliding windows of any number of sizes at once.
+#[derive(Clone, Debug)]
+pub struct DepthCounter {
+    stat: WindowStat,
+    prev: Option<u32>,
+    increases: u64,
+    /// The most recent depths, as many as the biggest window needs
+    recent: VecDeque<u32>,
+    biggest: usize,
+    windows: Vec<WindowCounter>,
+}
+
+#[derive(Clone, Debug)]
+struct WindowCounter {
+    size: usize,
+    /// Kept up to date as depths come and go, so sums and means don't need to go over the whole window
+    sum: u64,
+    prev: Option<f64>,
+    increases: u64,
+}
+
+impl DepthCounter {
+    /// Count increases between the sums of windows of 3, like part 2
+    pub fn new() -> DepthCounter {
+        DepthCounter::with_windows(&[WINDOW_SIZE], WindowStat::Sum)
+    }
+
+    /// Count increases between windows of each of the `sizes`, comparing them by `stat`
+    pub fn with_windows(sizes: &[usize], stat: WindowStat) -> DepthCounter {
+        assert!(
+            sizes.iter().all(|&size| size > 0),
+            "Windows need at least one depth"
+        );
+        DepthCounter {
+            stat,
+            prev: None,
+            increases: 0,
+            recent: VecDeque::new(),
+            biggest: sizes.iter().copied().max().unwrap_or(0),
+            windows: sizes
+                .iter()
+                .map(|&size| WindowCounter {
+                    size,
+                    sum: 0,
+                    prev: None,
+                    increases: 0,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn push(&mut self, depth: u32) {
+        if matches!(self.prev, Some(prev) if depth > prev) {
+            self.increases += 1;
+        }
+        self.prev = Some(depth);
+
+        //Until the oldest depth is dropped, `recent` still has the one leaving each window
+        self.recent.push_back(depth);
+        let len = self.recent.len();
+        for window in self.windows.iter_mut() {
+            window.sum += u64::from(depth);
+            if len > window.size {
+                window.sum -= u64::from(self.recent[len - 1 - window.size]);
+            } else if len < window.size {
+                continue;
+            }
+            let value = match self.stat {
+                WindowStat::Sum => window.sum as f64,
+                WindowStat::Mean => window.sum as f64 / window.size as f64,
+                stat => stat.apply(self.recent.range(len - window.size..).copied()),
+            };
+            if matches!(window.prev, Some(prev) if value > prev) {
+                window.increases += 1;
+            }
+            window.prev = Some(value);
+        }
+        if len > self.biggest {
+            self.recent.pop_front();
+        }
+    }
+
+    /// The number of times a depth was deeper than the one before
+    pub fn increases(&self) -> u64 {
+        self.increases
+    }
+
+    /// For each window size, the number of times a window's statistic was bigger than the one before
+    pub fn window_increases(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.windows.iter().map(|w| (w.size, w.increases))
+    }
+}
+
+impl Default for DepthCounter {
+    fn default() -> Self {
+        DepthCounter::new()
+    }
+}
+
+/// Count the increases for every window size in a single pass over the depths
+pub fn window_report(depths: &[u32], sizes: &[usize], stat: WindowStat) -> Vec<(usize, u64)> {
+    let mut counter = DepthCounter::with_windows(sizes, stat);
+    depths.iter().for_each(|&depth| counter.push(depth));
+    counter.window_increases().collect()
+}
+
+/// One depth read by `stream()`, with the running count of increases including it.
+/// The window counts are on `DepthStream::counter()`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Reading {
+    /// Starting at 1, to match the error line numbers
+    pub line: usize,
+    pub depth: u32,
+    pub increases: u64,
+}
+
+/// Read depths one line at a time as they arrive, eg from stdin, rather than waiting for the whole input,
+/// and keep `counter` up to date with them.
+/// Malformed lines come out as errors without stopping the stream, but it ends after any error reading
+/// from `reader`. Blank lines are skipped.
+pub fn stream<R: BufRead>(reader: R, counter: DepthCounter) -> DepthStream<R> {
+    DepthStream {
+        reader,
+        line_idx: 0,
+        counter,
+        finished: false,
+    }
+}
+
+/// Created by `stream()`
+pub struct DepthStream<R: BufRead> {
+    reader: R,
+    line_idx: usize,
+    counter: DepthCounter,
+    finished: bool,
+}
+
+impl<R: BufRead> DepthStream<R> {
+    /// The running totals for every depth read so far
+    pub fn counter(&self) -> &DepthCounter {
+        &self.counter
+    }
+}
+
+impl<R: BufRead> Iterator for DepthStream<R> {
+    type Item = PuzzleResult<Reading>;
+
+    fn next(&mut self) -> Option<PuzzleResult<Reading>> {
+        let mut line = String::new();
+        while !self.finished {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => self.finished = true,
+                Ok(_) => {
+                    let line_idx = self.line_idx;
+                    self.line_idx += 1;
+                    let line = line.trim_end_matches(&['\r', '\n'][..]);
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+
+                    return Some(
+                        parse_number(line, line.trim())
+                            .map(|depth| {
+                                self.counter.push(depth);
+                                Reading {
+                                    line: line_idx + 1,
+                                    depth,
+                                    increases: self.counter.increases(),
+                                }
+                            })
+                            .map_err(|e| PuzzleError::at_line(NAME, line_idx, e)),
+                    );
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(PuzzleError::Input {
+                        path: String::from("<stream>"),
+                        reason: e.to_string(),
+                    }));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DepthDirection {
     Up,
     Down,
     NoChange,
 }
 
+impl DepthDirection {
+    /// Which way the depth went from `prev` to `depth`, treating any change smaller than `tolerance` as noise.
+    /// With a tolerance of 0 or 1, only equal depths are no change.
+    pub fn between(prev: u32, depth: u32, tolerance: u32) -> DepthDirection {
+        if depth == prev || depth.abs_diff(prev) < tolerance {
+            DepthDirection::NoChange
+        } else if depth > prev {
+            DepthDirection::Up
+        } else {
+            DepthDirection::Down
+        }
+    }
+}
+
+/// Consecutive changes in the same direction
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Run {
+    /// Input line of the reading the run started from
+    pub start: usize,
+    /// The number of changes in the run, which is one less than the number of readings it covers
+    pub steps: usize,
+}
+
+/// A change in depth between two consecutive readings
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Change {
+    /// Input line of the reading before the change
+    pub from: usize,
+    pub amount: u32,
+}
+
+/// A summary of how the depth changes from each reading to the next, built up one depth at a time.
+/// Changes smaller than the noise tolerance count as no change at all.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DirectionStats {
+    tolerance: u32,
+    /// The line and depth of the last reading
+    prev: Option<(usize, u32)>,
+    current: Option<(DepthDirection, Run)>,
+    pub ups: u64,
+    pub downs: u64,
+    pub no_changes: u64,
+    /// The first of the longest, if there are several
+    pub longest_ascent: Option<Run>,
+    pub longest_descent: Option<Run>,
+    pub largest_increase: Option<Change>,
+    pub largest_decrease: Option<Change>,
+}
+
+impl DirectionStats {
+    pub fn new(tolerance: u32) -> DirectionStats {
+        DirectionStats {
+            tolerance,
+            prev: None,
+            current: None,
+            ups: 0,
+            downs: 0,
+            no_changes: 0,
+            longest_ascent: None,
+            longest_descent: None,
+            largest_increase: None,
+            largest_decrease: None,
+        }
+    }
+
+    /// Add the depth read from the given input line, which should come after the last one
+    pub fn push(&mut self, line: usize, depth: u32) {
+        if let Some((prev_line, prev)) = self.prev {
+            let change = Change {
+                from: prev_line,
+                amount: depth.abs_diff(prev),
+            };
+            match DepthDirection::between(prev, depth, self.tolerance) {
+                DepthDirection::NoChange => {
+                    self.no_changes += 1;
+                    self.current = None;
+                }
+                direction => self.record(direction, change),
+            }
+        }
+        self.prev = Some((line, depth));
+    }
+
+    /// Count a change up or down, and extend the run it's part of
+    fn record(&mut self, direction: DepthDirection, change: Change) {
+        let (count, largest, longest) = if direction == DepthDirection::Up {
+            (
+                &mut self.ups,
+                &mut self.largest_increase,
+                &mut self.longest_ascent,
+            )
+        } else {
+            (
+                &mut self.downs,
+                &mut self.largest_decrease,
+                &mut self.longest_descent,
+            )
+        };
+
+        *count += 1;
+        if !matches!(largest, Some(l) if l.amount >= change.amount) {
+            *largest = Some(change);
+        }
+
+        let run = match &mut self.current {
+            Some((d, run)) if *d == direction => {
+                run.steps += 1;
+                *run
+            }
+            _ => {
+                let run = Run {
+                    start: change.from,
+                    steps: 1,
+                };
+                self.current = Some((direction, run));
+                run
+            }
+        };
+        if !matches!(longest, Some(l) if l.steps >= run.steps) {
+            *longest = Some(run);
+        }
+    }
+}
+
+/// Work out the direction stats for a whole list of depths at once, one per line like the puzzle input
+pub fn analyze_directions(depths: &[u32], tolerance: u32) -> DirectionStats {
+    let mut stats = DirectionStats::new(tolerance);
+    for (line_idx, &depth) in depths.iter().enumerate() {
+        stats.push(line_idx + 1, depth);
+    }
+    stats
+}
+
+impl std::fmt::Display for DirectionStats {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        writeln!(
+            fmt,
+            "ups={}, downs={}, no_changes={} (tolerance {})",
+            self.ups, self.downs, self.no_changes, self.tolerance
+        )?;
+        let runs = [
+            ("longest ascent", self.longest_ascent),
+            ("longest descent", self.longest_descent),
+        ];
+        for (name, run) in runs {
+            match run {
+                Some(run) => writeln!(
+                    fmt,
+                    "{}: {} {} from line {}",
+                    name,
+                    run.steps,
+                    if run.steps == 1 { "step" } else { "steps" },
+                    run.start
+                )?,
+                None => writeln!(fmt, "{}: none", name)?,
+            }
+        }
+        let changes = [
+            ("largest increase", self.largest_increase),
+            ("largest decrease", self.largest_decrease),
+        ];
+        for (name, change) in changes {
+            match change {
+                Some(change) => writeln!(
+                    fmt,
+                    "{}: {} after line {}",
+                    name, change.amount, change.from
+                )?,
+                None => writeln!(fmt, "{}: none", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    pub fn bad_depth_reports_line() {
+        match Sonar.parse("100\n101\n1o2\n103") {
+            Err(PuzzleError::Parse {
+                line, column, text, ..
+            }) => {
+                assert_eq!(3, line);
+                assert_eq!(1, column);
+                assert_eq!("1o2", text);
+            }
+            other => panic!("Expected a parse error but got {:?}", other),
+        }
+    }
+
+    const SAMPLE: &str = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+
+    #[test]
+    pub fn stream_matches_parts() {
+        let mut depths = stream(SAMPLE.as_bytes(), DepthCounter::new());
+        let readings: Vec<Reading> = depths.by_ref().map(|r| r.unwrap()).collect();
+        assert_eq!(10, readings.len());
+        let last = &readings[9];
+        assert_eq!((10, 263, 7), (last.line, last.depth, last.increases));
+        assert_eq!(
+            vec![(3, 5)],
+            depths.counter().window_increases().collect::<Vec<_>>()
+        );
+
+        let depths = Sonar.parse(SAMPLE).unwrap();
+        assert_eq!(Ok(Answer::new("increases", 7u32)), Sonar.part1(&depths));
+        assert_eq!(
+            Ok(Answer::new("window_increases", 5u32)),
+            Sonar.part2(&depths)
+        );
+    }
+
+    #[test]
+    pub fn stream_skips_malformed_lines() {
+        let input = "199\n200\n2o8\n\n210\r\n";
+        let results: Vec<PuzzleResult<Reading>> =
+            stream(input.as_bytes(), DepthCounter::new()).collect();
+        assert_eq!(4, results.len());
+        match &results[2] {
+            Err(PuzzleError::Parse { line, text, .. }) => {
+                assert_eq!(3, *line);
+                assert_eq!("2o8", text);
+            }
+            other => panic!("Expected a parse error but got {:?}", other),
+        }
+        let last = results[3].as_ref().unwrap();
+        assert_eq!((5, 210, 2), (last.line, last.depth, last.increases));
+    }
+
+    #[test]
+    pub fn window_stats_work() {
+        let window = || vec![5u32, 1, 4, 2].into_iter();
+        assert_eq!(12.0, WindowStat::Sum.apply(window()));
+        assert_eq!(3.0, WindowStat::Mean.apply(window()));
+        assert_eq!(1.0, WindowStat::Min.apply(window()));
+        assert_eq!(5.0, WindowStat::Max.apply(window()));
+        assert_eq!(3.0, WindowStat::Median.apply(window()));
+        assert_eq!(4.0, WindowStat::Median.apply(vec![5, 1, 4].into_iter()));
+
+        //Far more than a u32 could hold
+        let deep = vec![u32::MAX; 4].into_iter();
+        assert_eq!(4.0 * u32::MAX as f64, WindowStat::Sum.apply(deep));
+
+        assert_eq!(Ok(WindowStat::Median), "MEDIAN".parse());
+        assert!("mode".parse::<WindowStat>().is_err());
+    }
+
+    #[test]
+    pub fn window_report_works() {
+        let depths = Sonar.parse(SAMPLE).unwrap();
+        //A window of 1 is just the depths themselves
+        assert_eq!(
+            vec![(1, 7), (3, 5), (10, 0)],
+            window_report(&depths, &[1, 3, 10], WindowStat::Sum)
+        );
+        assert_eq!(vec![(3, 5)], window_report(&depths, &[3], WindowStat::Mean));
+        //Medians of 3: 200, 208, 208, 207, 207, 240, 260, 263
+        assert_eq!(
+            vec![(3, 4)],
+            window_report(&depths, &[3], WindowStat::Median)
+        );
+        //Maxima of 2: 200, 208, 210, 210, 207, 240, 269, 269, 263
+        assert_eq!(vec![(2, 4)], window_report(&depths, &[2], WindowStat::Max));
+    }
+
+    #[test]
+    pub fn running_sums_match_whole_windows() {
+        let depths = Sonar.parse(SAMPLE).unwrap();
+        for stat in [WindowStat::Sum, WindowStat::Mean] {
+            let mut counter = DepthCounter::with_windows(&[1, 2, 3, 4], stat);
+            for (i, &depth) in depths.iter().enumerate() {
+                counter.push(depth);
+                for window in &counter.windows {
+                    if i + 1 >= window.size {
+                        let whole = stat.apply(depths[i + 1 - window.size..=i].iter().copied());
+                        assert_eq!(Some(whole), window.prev);
+                    }
+                }
+            }
+        }
+        //Deeper than a u32 can hold once summed
+        let mut counter = DepthCounter::with_windows(&[2], WindowStat::Sum);
+        [u32::MAX, u32::MAX, u32::MAX]
+            .iter()
+            .for_each(|&d| counter.push(d));
+        assert_eq!(Some(2.0 * u32::MAX as f64), counter.windows[0].prev);
+    }
+
+    #[test]
+    pub fn direction_stats_work() {
+        let depths = Sonar.parse(SAMPLE).unwrap();
+        let stats = analyze_directions(&depths, 0);
+        assert_eq!((7, 2, 0), (stats.ups, stats.downs, stats.no_changes));
+        //199 -> 210 and 200 -> 269 are both 3 steps, so the first one wins
+        assert_eq!(Some(Run { start: 1, steps: 3 }), stats.longest_ascent);
+        assert_eq!(Some(Run { start: 4, steps: 1 }), stats.longest_descent);
+        assert_eq!(
+            Some(Change {
+                from: 6,
+                amount: 33
+            }),
+            stats.largest_increase
+        );
+        assert_eq!(
+            Some(Change {
+                from: 4,
+                amount: 10
+            }),
+            stats.largest_decrease
+        );
+        let summary = stats.to_string();
+        assert!(summary.contains("longest ascent: 3 steps from line 1"));
+        assert!(summary.contains("longest descent: 1 step from line 4"));
+        assert!(summary.contains("largest increase: 33 after line 6"));
+    }
+
+    #[test]
+    pub fn direction_stats_ignore_noise() {
+        let depths = Sonar.parse(SAMPLE).unwrap();
+        //A tolerance of 1 only ignores equal depths, of which there aren't any
+        let stats = analyze_directions(&depths, 1);
+        assert_eq!((7, 2, 0), (stats.ups, stats.downs, stats.no_changes));
+
+        //The first step of the first ascent is only 1
+        let stats = analyze_directions(&depths, 2);
+        assert_eq!((6, 2, 1), (stats.ups, stats.downs, stats.no_changes));
+        assert_eq!(Some(Run { start: 5, steps: 3 }), stats.longest_ascent);
+
+        //The drop of exactly 10 isn't noise, but the drop of 9 is
+        let stats = analyze_directions(&depths, 10);
+        assert_eq!((2, 1, 6), (stats.ups, stats.downs, stats.no_changes));
+        assert_eq!(Some(Run { start: 6, steps: 2 }), stats.longest_ascent);
+        assert_eq!(
+            Some(Change {
+                from: 4,
+                amount: 10
+            }),
+            stats.largest_decrease
+        );
+
+        let stats = analyze_directions(&depths, 100);
+        assert_eq!(9, stats.no_changes);
+        assert_eq!(None, stats.longest_ascent);
+        assert_eq!(None, stats.largest_increase);
+        assert!(stats.to_string().contains("longest ascent: none"));
+    }
+
+    #[test]
+    pub fn change_at_tolerance_is_not_noise() {
+        assert_eq!(DepthDirection::Up, DepthDirection::between(100, 103, 3));
+        assert_eq!(DepthDirection::Down, DepthDirection::between(103, 100, 3));
+        assert_eq!(
+            DepthDirection::NoChange,
+            DepthDirection::between(100, 102, 3)
+        );
+        assert_eq!(
+            DepthDirection::NoChange,
+            DepthDirection::between(100, 100, 0)
+        );
+        assert_eq!(DepthDirection::Up, DepthDirection::between(100, 101, 1));
+    }
+
+    #[test]
+    pub fn direction_stats_use_input_lines() {
+        let input = "199\n2o0\n\n208\n210\n200\n";
+        let mut stats = DirectionStats::new(0);
+        for reading in stream(input.as_bytes(), DepthCounter::new()).flatten() {
+            stats.push(reading.line, reading.depth);
+        }
+        //The bad line and the blank one still count towards the positions
+        assert_eq!(Some(Run { start: 1, steps: 2 }), stats.longest_ascent);
+        assert_eq!(Some(Change { from: 1, amount: 9 }), stats.largest_increase);
+        assert_eq!(
+            Some(Change {
+                from: 5,
+                amount: 10
+            }),
+            stats.largest_decrease
+        );
+        assert!(stats
+            .to_string()
+            .contains("longest descent: 1 step from line 5"));
+    }
+
+    /// Gives some depths, then fails, like a pipe that breaks part way through
+    struct BrokenPipe {
+        sent: bool,
+    }
+
+    impl std::io::Read for BrokenPipe {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.sent {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "pipe broke",
+                ));
+            }
+            self.sent = true;
+            let depths = b"100\n101\n";
+            buf[..depths.len()].copy_from_slice(depths);
+            Ok(depths.len())
+        }
+    }
+
+    #[test]
+    pub fn stream_stops_at_read_error() {
+        let reader = std::io::BufReader::new(BrokenPipe { sent: false });
+        let results: Vec<PuzzleResult<Reading>> = stream(reader, DepthCounter::new()).collect();
+        assert_eq!(3, results.len());
+        assert_eq!(1, results[1].as_ref().unwrap().increases);
+        assert!(matches!(results[2], Err(PuzzleError::Input { .. })));
+    }
+
     #[test]
     pub fn empty() {
-        let input = vec![];
+        let input: Vec<u32> = vec![];
         let result: Vec<_> = calculate_direction(&mut input.into_iter()).collect();
         assert_eq!(result, vec![]);
     }
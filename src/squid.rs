@@ -1,81 +1,150 @@
+use crate::answer::Answer;
+use crate::error::{parse_number, parse_separated, LineError, PuzzleError, PuzzleResult};
+use crate::solver::Solver;
 use itertools::Itertools;
 use std::fmt::Display;
 
-pub fn run(input: String) -> Result<String, String> {
+const NAME: &str = "squid";
+const BOARD_SIZE: usize = 5;
+
+pub struct Squid;
+
+impl Solver for Squid {
+    type Input = Game;
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn day(&self) -> usize {
+        4
+    }
+
+    fn input_file(&self) -> &'static str {
+        "squid.txt"
+    }
+
+    fn parse(&self, input: &str) -> PuzzleResult<Game> {
+        parse_game(input)
+    }
+
+    fn part1(&self, game: &Game) -> PuzzleResult<Answer> {
+        find_first_winner(&game.draws, game.boards.clone())
+    }
+
+    fn part2(&self, game: &Game) -> PuzzleResult<Answer> {
+        find_last_winner(&game.draws, game.boards.clone())
+    }
+}
+
+/// The numbers to be drawn, and the boards before any of them are marked
+#[derive(Debug)]
+pub struct Game {
+    draws: Vec<u8>,
+    boards: Vec<BingoBoard>,
+}
+
+fn parse_game(input: &str) -> PuzzleResult<Game> {
     let lines: Vec<&str> = input.lines().collect();
-    let draws: Vec<u8> = lines[0]
-        .split(',')
-        .map(|s| s.parse::<u8>().unwrap())
-        .collect();
-
-    let boards: Vec<BingoBoard> = lines
-        .iter()
-        .skip(1)
-        .chunks(6)
-        .into_iter()
-        .map(|boarditer| {
-            let board_squares: Vec<u8> = boarditer
-                .skip(1)
-                .flat_map(|line| line.split_whitespace())
-                .map(|s| s.parse::<u8>().unwrap())
-                .collect();
-            BingoBoard::new(5, board_squares)
+    let draw_line = lines
+        .first()
+        .ok_or_else(|| PuzzleError::unsolvable(NAME, "the input was empty"))?;
+    let draws: Vec<u8> =
+        parse_separated(draw_line, ',').map_err(|e| PuzzleError::at_line(NAME, 0, e))?;
+
+    //Boards are separated by blank lines, but just count off rows rather than relying on that
+    let mut boards: Vec<BingoBoard> = vec![];
+    let mut board_squares: Vec<u8> = Vec::with_capacity(BOARD_SIZE * BOARD_SIZE);
+    for (idx, line) in lines.iter().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let row = parse_row(line).map_err(|e| PuzzleError::at_line(NAME, idx, e))?;
+        board_squares.extend(row);
+        if board_squares.len() == BOARD_SIZE * BOARD_SIZE {
+            boards.push(BingoBoard::new(
+                BOARD_SIZE,
+                std::mem::take(&mut board_squares),
+            ));
+        }
+    }
+
+    if !board_squares.is_empty() {
+        let last_line = lines.len() - 1;
+        return Err(PuzzleError::at_line(
+            NAME,
+            last_line,
+            LineError::new(
+                lines[last_line],
+                lines[last_line],
+                format!("the last board has fewer than {} rows", BOARD_SIZE),
+            ),
+        ));
+    }
+
+    Ok(Game { draws, boards })
+}
+
+fn parse_row(line: &str) -> Result<Vec<u8>, LineError> {
+    let row = line
+        .split_whitespace()
+        .map(|token| {
+            let val = parse_number::<u8>(line, token)?;
+            if val > VALUE_MASK {
+                Err(LineError::new(
+                    line,
+                    token,
+                    format!("squares can't be larger than {}", VALUE_MASK),
+                ))
+            } else {
+                Ok(val)
+            }
         })
-        .collect();
-
-    //Finds the first winning board (ie, part 1)
-    // let mut draws_made: Vec<u8> = Vec::with_capacity(draws.len());
-    // if let ControlFlow::Break((winning_draw_idx, winning_board_idx)) = draws
-    //     .iter()
-    //     .enumerate()
-    //     .try_fold::<(usize, usize), _, _>((0, 0), |_, (draw_idx, &draw)| {
-    //         draws_made.push(draw);
-    //         if let ControlFlow::Break(winning_board_idx) = boards
-    //             .iter_mut()
-    //             .enumerate()
-    //             .try_fold::<usize, _, _>(0, |_, (board_idx, board)| {
-    //                 board.record_draw(draw);
-    //                 if board.has_won() {
-    //                     ControlFlow::Break(board_idx)
-    //                 } else {
-    //                     ControlFlow::Continue(0)
-    //                 }
-    //             })
-    //         {
-    //             ControlFlow::Break((draw_idx, winning_board_idx))
-    //         } else {
-    //             ControlFlow::Continue((0, 0))
-    //         }
-    //     })
-    // {
-    //     let winning_board = &boards[winning_board_idx];
-    //     let winning_number = *(&draws[winning_draw_idx]);
-    //     let unmarked_numbers = winning_board.get_unmarked_numbers();
-    //     let unmarked_sum: u64 = unmarked_numbers.iter().map(|b| *b as u64).sum();
-    //     let score = unmarked_sum * winning_number as u64;
-
-    //     println!("Draws made: {}", draws_made.iter().join(", "));
-    //     for board in &boards {
-    //         if !board.has_won() {
-    //             println!("Losing board:\n{}", board);
-    //         }
-    //     }
-    //     println!("Winning number: {}", winning_number);
-    //     println!("Winning board: \n{}", winning_board);
-    //     println!("Unmarked numbers: {}", unmarked_numbers.iter().join(", "));
-    //     println!("Unmarked sum: {}", unmarked_sum);
-    //     println!("Score: {}", score);
-    //     return Ok(format!("Score: {}", score));
-    // } else {
-    //     return Err(String::from("Failed to find a winner"));
-    // }
-
-    // Find the _last_ winning board (ie, part 2)
+        .collect::<Result<Vec<u8>, LineError>>()?;
+
+    if row.len() == BOARD_SIZE {
+        Ok(row)
+    } else {
+        Err(LineError::new(
+            line,
+            line,
+            format!("expected {} numbers in each row", BOARD_SIZE),
+        ))
+    }
+}
+
+/// Finds the first winning board (ie, part 1)
+fn find_first_winner(draws: &[u8], mut boards: Vec<BingoBoard>) -> PuzzleResult<Answer> {
+    for &draw in draws {
+        for board in &mut boards {
+            board.record_draw(draw);
+        }
+
+        if let Some(winning_board) = boards.iter().find(|b| b.has_won()) {
+            let unmarked_numbers = winning_board.get_unmarked_numbers();
+            let unmarked_sum: u64 = unmarked_numbers.iter().map(|b| *b as u64).sum();
+            let score = unmarked_sum * draw as u64;
+
+            log::debug!("Winning number: {}", draw);
+            log::debug!("Winning board: \n{}", winning_board);
+            log::debug!("Unmarked numbers: {}", unmarked_numbers.iter().join(", "));
+            log::debug!("Unmarked sum: {}", unmarked_sum);
+            log::debug!("Score: {}", score);
+            return Ok(Answer::new("score", score));
+        }
+    }
+
+    Err(PuzzleError::unsolvable(NAME, "no board ever won"))
+}
+
+/// Finds the _last_ winning board (ie, part 2)
+fn find_last_winner(draws: &[u8], boards: Vec<BingoBoard>) -> PuzzleResult<Answer> {
     let draws_made: Vec<u8> = Vec::with_capacity(draws.len());
     let mut non_won_boards = boards;
     let mut last_number: Option<u8> = None;
 
-    for draw in draws {
+    for &draw in draws {
         if non_won_boards.len() > 1 {
             for board in &mut non_won_boards {
                 board.record_draw(draw);
@@ -84,8 +153,7 @@ pub fn run(input: String) -> Result<String, String> {
                 .into_iter()
                 .filter(|b| !b.has_won())
                 .collect();
-        } else {
-            let lb = non_won_boards.get_mut(0).unwrap();
+        } else if let Some(lb) = non_won_boards.get_mut(0) {
             lb.record_draw(draw);
             if lb.has_won() {
                 last_number = Some(draw);
@@ -94,18 +162,26 @@ pub fn run(input: String) -> Result<String, String> {
         }
     }
 
-    let last_board = &non_won_boards[0];
+    let (last_board, last_number) = match (non_won_boards.first(), last_number) {
+        (Some(board), Some(number)) => (board, number),
+        _ => {
+            return Err(PuzzleError::unsolvable(
+                NAME,
+                "there wasn't a single board that won last",
+            ))
+        }
+    };
     let unmarked_numbers = last_board.get_unmarked_numbers();
     let unmarked_sum: u64 = unmarked_numbers.iter().map(|b| *b as u64).sum();
-    let score = unmarked_sum * last_number.unwrap() as u64;
-
-    println!("Draws made: {}", draws_made.iter().join(", "));
-    println!("Last number: {}", last_number.unwrap());
-    println!("Last board: \n{}", last_board);
-    println!("Unmarked numbers: {}", unmarked_numbers.iter().join(", "));
-    println!("Unmarked sum: {}", unmarked_sum);
-    println!("Score: {}", score);
-    return Ok(format!("Score: {}", score));
+    let score = unmarked_sum * last_number as u64;
+
+    log::debug!("Draws made: {}", draws_made.iter().join(", "));
+    log::debug!("Last number: {}", last_number);
+    log::debug!("Last board: \n{}", last_board);
+    log::debug!("Unmarked numbers: {}", unmarked_numbers.iter().join(", "));
+    log::debug!("Unmarked sum: {}", unmarked_sum);
+    log::debug!("Score: {}", score);
+    Ok(Answer::new("score", score))
 }
 
 const MARK_MASK: u8 = 0b10000000;
@@ -233,6 +309,56 @@ impl Display for BingoBoard {
 mod test {
     use super::*;
 
+    const SAMPLE: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
+
+    #[test]
+    pub fn sample_part1() {
+        let game = Squid.parse(SAMPLE).unwrap();
+        assert_eq!(Ok(Answer::new("score", 4512u32)), Squid.part1(&game));
+    }
+
+    #[test]
+    pub fn sample_part2() {
+        let game = Squid.parse(SAMPLE).unwrap();
+        assert_eq!(Ok(Answer::new("score", 1924u32)), Squid.part2(&game));
+    }
+
+    #[test]
+    pub fn bad_board_reports_location() {
+        let input = "1,2,3\n\n1 2 3 4 5\n6 7 8 9 10\n11 12 x 14 15";
+        match parse_game(input) {
+            Err(PuzzleError::Parse {
+                line, column, text, ..
+            }) => {
+                assert_eq!(5, line);
+                assert_eq!(7, column);
+                assert_eq!("x", text);
+            }
+            other => panic!("Expected a parse error but got {:?}", other),
+        }
+
+        parse_game("1,2,3\n\n1 2 3 4 5\n6 7 8 9 10").expect_err("The board is missing rows");
+        parse_game("1,2,3\n\n1 2 3 4 5 6").expect_err("The row has too many squares");
+    }
+
     #[test]
     pub fn can_mark_square_and_get_value() {
         let mut sq = BingoSquare::new(127);
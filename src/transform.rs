@@ -0,0 +1,266 @@
+use crate::array_grid::ArrayGrid;
+
+impl<T> ArrayGrid<T>
+where
+    T: Clone + Default,
+{
+    /// Swap the rows and columns, so that (x, y) moves to (y, x)
+    pub fn transpose(&self) -> ArrayGrid<T> {
+        self.remap(self.height(), self.width(), |x, y| (y, x))
+    }
+
+    /// Turn the grid a quarter turn clockwise, so the left column becomes the top row
+    pub fn rotate_clockwise(&self) -> ArrayGrid<T> {
+        let height = self.height();
+        self.remap(height, self.width(), |x, y| (y, height - 1 - x))
+    }
+
+    /// Turn the grid upside down
+    pub fn rotate_half(&self) -> ArrayGrid<T> {
+        let (width, height) = (self.width(), self.height());
+        self.remap(width, height, |x, y| (width - 1 - x, height - 1 - y))
+    }
+
+    /// Turn the grid a quarter turn anticlockwise, so the right column becomes the top row
+    pub fn rotate_anticlockwise(&self) -> ArrayGrid<T> {
+        let width = self.width();
+        self.remap(self.height(), width, |x, y| (width - 1 - y, x))
+    }
+
+    /// Mirror the grid left to right
+    pub fn flip_horizontal(&self) -> ArrayGrid<T> {
+        let width = self.width();
+        self.remap(width, self.height(), |x, y| (width - 1 - x, y))
+    }
+
+    /// Mirror the grid top to bottom
+    pub fn flip_vertical(&self) -> ArrayGrid<T> {
+        let height = self.height();
+        self.remap(self.width(), height, |x, y| (x, height - 1 - y))
+    }
+
+    /// Repeat the grid `across` times horizontally and `down` times vertically. Each cell in the tile at
+    /// (tile_x, tile_y) is whatever `transform(tile_x, tile_y, cell)` gives for the original cell.
+    pub fn tile(
+        &self,
+        across: usize,
+        down: usize,
+        transform: impl Fn(usize, usize, &T) -> T,
+    ) -> ArrayGrid<T> {
+        let (width, height) = (self.width(), self.height());
+        let mut arr = Vec::with_capacity(width * across * height * down);
+        for y in 0..height * down {
+            for x in 0..width * across {
+                arr.push(transform(
+                    x / width,
+                    y / height,
+                    self.get(x % width, y % height),
+                ));
+            }
+        }
+        ArrayGrid::create_from(width * across, height * down, arr)
+    }
+
+    /// A borrowed window onto the `width` x `height` rectangle with its top left corner at (x, y),
+    /// without copying any of the cells
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> GridView<'_, T> {
+        assert!(
+            x + width <= self.width() && y + height <= self.height(),
+            "A {}x{} view at ({}, {}) doesn't fit in a {}x{} grid",
+            width,
+            height,
+            x,
+            y,
+            self.width(),
+            self.height()
+        );
+        GridView {
+            grid: self,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Build a `width` x `height` grid where each cell is a copy of the cell at `source(x, y)` in this one
+    fn remap(
+        &self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> (usize, usize),
+    ) -> ArrayGrid<T> {
+        let mut arr = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = source(x, y);
+                arr.push(self.get(sx, sy).clone());
+            }
+        }
+        ArrayGrid::create_from(width, height, arr)
+    }
+}
+
+/// A rectangle within an `ArrayGrid`, with its own coordinates starting from (0, 0) at its top left corner.
+/// Create one with `ArrayGrid::view()`.
+#[derive(Debug)]
+pub struct GridView<'a, T>
+where
+    T: Clone + Default,
+{
+    grid: &'a ArrayGrid<T>,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+//Derived impls would need T: Copy, but only the reference is copied
+impl<T> Clone for GridView<'_, T>
+where
+    T: Clone + Default,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> where T: Clone + Default {}
+
+impl<'a, T> GridView<'a, T>
+where
+    T: Clone + Default,
+{
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &'a T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside a {}x{} view",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        self.grid.get(self.x + x, self.y + y)
+    }
+
+    pub fn get_checked(&self, x: i64, y: i64) -> Option<&'a T> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            Some(self.get(x as usize, y as usize))
+        }
+    }
+
+    /// Every cell in the view, row by row
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + 'a {
+        let view = *self;
+        (0..self.height).flat_map(move |y| (0..view.width).map(move |x| view.get(x, y)))
+    }
+
+    /// A smaller view within this one, positioned relative to this one's top left corner
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> GridView<'a, T> {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "A {}x{} view at ({}, {}) doesn't fit in a {}x{} view",
+            width,
+            height,
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        GridView {
+            grid: self.grid,
+            x: self.x + x,
+            y: self.y + y,
+            width,
+            height,
+        }
+    }
+
+    /// Copy the cells in the view into a grid of their own
+    pub fn to_array_grid(&self) -> ArrayGrid<T> {
+        ArrayGrid::create_from(self.width, self.height, self.iter().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 0 1 2
+    /// 3 4 5
+    fn numbered_grid() -> ArrayGrid<u8> {
+        ArrayGrid::create_from(3, 2, (0..6).collect())
+    }
+
+    #[test]
+    pub fn transpose_works() {
+        assert_eq!("03\n14\n25", numbered_grid().transpose().to_string());
+    }
+
+    #[test]
+    pub fn rotations_work() {
+        let grid = numbered_grid();
+        assert_eq!("30\n41\n52", grid.rotate_clockwise().to_string());
+        assert_eq!("543\n210", grid.rotate_half().to_string());
+        assert_eq!("25\n14\n03", grid.rotate_anticlockwise().to_string());
+
+        let round_trip = grid
+            .rotate_clockwise()
+            .rotate_clockwise()
+            .rotate_clockwise()
+            .rotate_clockwise();
+        assert_eq!(grid.to_string(), round_trip.to_string());
+    }
+
+    #[test]
+    pub fn flips_work() {
+        let grid = numbered_grid();
+        assert_eq!("210\n543", grid.flip_horizontal().to_string());
+        assert_eq!("345\n012", grid.flip_vertical().to_string());
+    }
+
+    #[test]
+    pub fn tile_works() {
+        let grid: ArrayGrid<u8> = ArrayGrid::parse_digits("89").unwrap();
+        //The day 15 part 2 rule: each tile further right or down adds 1, wrapping from 9 back to 1
+        let tiled = grid.tile(2, 2, |tx, ty, &risk| {
+            (risk - 1 + tx as u8 + ty as u8) % 9 + 1
+        });
+        assert_eq!("8991\n9112", tiled.to_string());
+    }
+
+    #[test]
+    pub fn view_works() {
+        let grid: ArrayGrid<u8> = ArrayGrid::create_from(4, 4, (0..16).collect());
+        let view = grid.view(1, 1, 3, 2);
+        assert_eq!(3, view.width());
+        assert_eq!(2, view.height());
+        assert_eq!(&5, view.get(0, 0));
+        assert_eq!(&11, view.get(2, 1));
+        assert_eq!(None, view.get_checked(3, 0));
+        assert_eq!(Some(&9), view.get_checked(0, 1));
+        assert_eq!(
+            vec![5, 6, 7, 9, 10, 11],
+            view.iter().copied().collect::<Vec<_>>()
+        );
+
+        let inner = view.view(1, 1, 2, 1);
+        assert_eq!("10 11", inner.to_array_grid().to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn view_must_fit() {
+        numbered_grid().view(1, 0, 3, 1);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::answer::{Answer, Value};
+use crate::error::{PuzzleError, PuzzleResult};
+use crate::solver::{Part, Puzzle, PuzzleRun};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// The known-good answers for each puzzle, keyed by puzzle name.
+/// Every value in an answer is checked, eg
+/// ```toml
+/// [binary.part1]
+/// power = 3985686
+/// gamma = 2502
+/// epsilon = 1593
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(transparent)]
+pub struct Answers {
+    puzzles: HashMap<String, PuzzleAnswers>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct PuzzleAnswers {
+    part1: Option<Expected>,
+    part2: Option<Expected>,
+}
+
+/// The values an answer should have, by name
+type Expected = BTreeMap<String, Value>;
+
+impl Answers {
+    pub fn load(path: &Path) -> PuzzleResult<Answers> {
+        let to_error = |reason: String| PuzzleError::Input {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let raw = std::fs::read_to_string(path).map_err(|e| to_error(e.to_string()))?;
+        Answers::parse(&raw).map_err(to_error)
+    }
+
+    fn parse(raw: &str) -> Result<Answers, String> {
+        toml::from_str(raw).map_err(|e| e.to_string())
+    }
+
+    fn get(&self, puzzle: &str, part: Part) -> Option<&Expected> {
+        let answers = self.puzzles.get(puzzle)?;
+        match part {
+            Part::One => answers.part1.as_ref(),
+            Part::Two => answers.part2.as_ref(),
+        }
+    }
+}
+
+/// The result of comparing one part of a puzzle against its known-good answer
+#[derive(PartialEq, Eq, Debug)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+        actual: Answer,
+    },
+    /// There's no known-good answer to compare with
+    Missing {
+        actual: Answer,
+    },
+    /// The puzzle failed to produce an answer at all
+    Error(PuzzleError),
+    /// The part hasn't been solved yet, so there's nothing to check
+    NotImplemented,
+}
+
+impl Verdict {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Verdict::Fail { .. } | Verdict::Error(_))
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            Verdict::Pass => write!(fmt, "pass"),
+            Verdict::Fail { expected, actual } => {
+                write!(fmt, "FAIL expected \"{}\" but got \"{}\"", expected, actual)
+            }
+            Verdict::Missing { actual } => write!(fmt, "missing, got \"{}\"", actual),
+            Verdict::Error(e) => write!(fmt, "ERROR {}", e),
+            Verdict::NotImplemented => write!(fmt, "not implemented"),
+        }
+    }
+}
+
+/// Compare each part of a puzzle run against the known answers
+pub fn check(
+    answers: &Answers,
+    puzzle: &dyn Puzzle,
+    run: PuzzleResult<PuzzleRun>,
+    parts: &[Part],
+) -> Vec<(Part, Verdict)> {
+    let run = match run {
+        Ok(run) => run,
+        Err(e) => {
+            return parts
+                .iter()
+                .map(|&part| (part, Verdict::Error(e.clone())))
+                .collect()
+        }
+    };
+
+    run.parts
+        .into_iter()
+        .map(|part_run| {
+            let expected = answers.get(puzzle.name(), part_run.part);
+            let verdict = match (part_run.answer, expected) {
+                (Err(PuzzleError::NotImplemented { .. }), _) => Verdict::NotImplemented,
+                (Err(e), _) => Verdict::Error(e),
+                (Ok(actual), None) => Verdict::Missing { actual },
+                (Ok(actual), Some(expected)) if matches(&actual, expected) => Verdict::Pass,
+                (Ok(actual), Some(expected)) => Verdict::Fail {
+                    expected: expected
+                        .iter()
+                        .map(|(name, value)| format!("{}={}", name, value))
+                        .join(", "),
+                    actual,
+                },
+            };
+            (part_run.part, verdict)
+        })
+        .collect()
+}
+
+/// Whether the answer has exactly the expected values, no more and no less
+fn matches(actual: &Answer, expected: &Expected) -> bool {
+    actual.fields().count() == expected.len()
+        && actual
+            .fields()
+            .all(|(name, value)| expected.get(name) == Some(value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::find_by_name;
+
+    const ANSWERS: &str = r#"
+[whales.part1]
+median_fuel = 37
+median = 2
+
+[whales.part2]
+min_fuel = 1
+"#;
+
+    #[test]
+    pub fn parse_answers_works() {
+        let answers = Answers::parse(ANSWERS).unwrap();
+        let part1 = answers.get("whales", Part::One).unwrap();
+        assert_eq!(Some(&Value::Number(37)), part1.get("median_fuel"));
+        assert_eq!(Some(&Value::Number(2)), part1.get("median"));
+        assert_eq!(None, answers.get("sonar", Part::One));
+
+        Answers::parse("[whales.part3]\nfuel = 1").expect_err("There is no part 3");
+    }
+
+    #[test]
+    pub fn check_works() {
+        let answers = Answers::parse(ANSWERS).unwrap();
+        let whales = find_by_name("whales").unwrap();
+        let run = whales.run("16,1,2,0,4,2,7,1,2,14", &[Part::One, Part::Two]);
+
+        let verdicts = check(&answers, whales, run, &[Part::One, Part::Two]);
+        assert_eq!((Part::One, Verdict::Pass), verdicts[0]);
+        assert!(verdicts[1].1.is_failure());
+
+        //Extra values which aren't expected are a failure too
+        let expected = answers.get("whales", Part::One).unwrap();
+        assert!(matches(
+            &Answer::new("median_fuel", 37).with("median", 2),
+            expected
+        ));
+        assert!(!matches(&Answer::new("median_fuel", 37), expected));
+        assert!(!matches(
+            &Answer::new("median_fuel", 37)
+                .with("median", 2)
+                .with("mean", 5),
+            expected
+        ));
+
+        let sonar = find_by_name("sonar").unwrap();
+        let run = sonar.run("1\n2", &[Part::One]);
+        let verdicts = check(&answers, sonar, run, &[Part::One]);
+        assert_eq!(
+            (
+                Part::One,
+                Verdict::Missing {
+                    actual: Answer::new("increases", 1)
+                }
+            ),
+            verdicts[0]
+        );
+        assert!(!verdicts[0].1.is_failure());
+    }
+}
@@ -1,52 +1,91 @@
-pub fn run(input: String) -> Result<String, String> {
-    let mut crabs: Vec<i32> = input
-        .split(',')
-        .map(|c| c.parse::<i32>().unwrap())
-        .collect();
-    crabs.sort_unstable();
-
-    let median: i32;
-    if crabs.len() % 2 == 1 {
-        median = crabs[crabs.len() / 2];
-    } else {
-        let a_idx = crabs.len() / 2;
-        let b_idx = crabs.len() / 2 - 1;
-        let a = crabs[a_idx];
-        let b = crabs[b_idx];
-        median = (a + b) / 2;
+use crate::answer::Answer;
+use crate::error::{parse_lines, parse_separated, PuzzleError, PuzzleResult};
+use crate::solver::Solver;
+
+const NAME: &str = "whales";
+
+pub struct Whales;
+
+impl Solver for Whales {
+    type Input = Vec<i32>;
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn day(&self) -> usize {
+        7
+    }
+
+    fn input_file(&self) -> &'static str {
+        "whales.txt"
     }
 
-    let median_fuel: i32 = crabs.iter().map(|c| (c - median).abs()).sum();
-
-    let sum: i32 = crabs.iter().sum::<i32>();
-    let raw_mean = sum as f64 / crabs.len() as f64;
-    let mean_floor = raw_mean.floor() as i32;
-    let mean_ceil = raw_mean.ceil() as i32;
-
-    println!(
-        "sum={}, raw_mean={}, mean_floor={}, mean_ceil={}",
-        sum, raw_mean, mean_floor, mean_ceil
-    );
-
-    let fuel: (i32, i32) = crabs
-        .iter()
-        .map(|&start| {
-            //triangle numbers: n*(n+1)/2
-            let diff_floor = (start - mean_floor).abs() as f64;
-            let diff_ceil = (start - mean_ceil).abs() as f64;
-            let fuel_floor = (diff_floor * ((diff_floor + 1f64) / 2f64)) as i32;
-            let fuel_ceil = (diff_ceil * ((diff_ceil + 1f64) / 2f64)) as i32;
-            (fuel_floor, fuel_ceil)
-        })
-        .fold((0i32, 0i32), |(old_f, old_c), (new_f, new_c)| {
-            (old_f + new_f, old_c + new_c)
-        });
-    let fuel_floor = fuel.0;
-    let fuel_ceil = fuel.1;
-    let min_fuel = fuel_floor.min(fuel_ceil);
-
-    Ok(format!(
-        "median: {}, median_fuel: {}, mean_floor: {}, mean_ceil: {}, fuel_floor: {}, fuel_ceil: {}, min_fuel: {}",
-        median, median_fuel, mean_floor, mean_ceil, fuel_floor, fuel_ceil, min_fuel
-    ))
+    /// Parses the crab positions, and sorts them
+    fn parse(&self, input: &str) -> PuzzleResult<Vec<i32>> {
+        let mut crabs: Vec<i32> = vec![];
+        for line in parse_lines(NAME, input, |line| parse_separated::<i32>(line, ',')) {
+            crabs.extend(line?);
+        }
+
+        if crabs.is_empty() {
+            return Err(PuzzleError::unsolvable(NAME, "there are no crabs"));
+        }
+        crabs.sort_unstable();
+        Ok(crabs)
+    }
+
+    fn part1(&self, crabs: &Vec<i32>) -> PuzzleResult<Answer> {
+        let median: i32 = if crabs.len() % 2 == 1 {
+            crabs[crabs.len() / 2]
+        } else {
+            let a_idx = crabs.len() / 2;
+            let b_idx = crabs.len() / 2 - 1;
+            let a = crabs[a_idx];
+            let b = crabs[b_idx];
+            (a + b) / 2
+        };
+
+        let median_fuel: i32 = crabs.iter().map(|c| (c - median).abs()).sum();
+
+        Ok(Answer::new("median_fuel", median_fuel).with("median", median))
+    }
+
+    fn part2(&self, crabs: &Vec<i32>) -> PuzzleResult<Answer> {
+        let sum: i32 = crabs.iter().sum::<i32>();
+        let raw_mean = sum as f64 / crabs.len() as f64;
+        let mean_floor = raw_mean.floor() as i32;
+        let mean_ceil = raw_mean.ceil() as i32;
+
+        log::debug!(
+            "sum={}, raw_mean={}, mean_floor={}, mean_ceil={}",
+            sum,
+            raw_mean,
+            mean_floor,
+            mean_ceil
+        );
+
+        let fuel: (i32, i32) = crabs
+            .iter()
+            .map(|&start| {
+                //triangle numbers: n*(n+1)/2
+                let diff_floor = (start - mean_floor).abs() as f64;
+                let diff_ceil = (start - mean_ceil).abs() as f64;
+                let fuel_floor = (diff_floor * ((diff_floor + 1f64) / 2f64)) as i32;
+                let fuel_ceil = (diff_ceil * ((diff_ceil + 1f64) / 2f64)) as i32;
+                (fuel_floor, fuel_ceil)
+            })
+            .fold((0i32, 0i32), |(old_f, old_c), (new_f, new_c)| {
+                (old_f + new_f, old_c + new_c)
+            });
+        let fuel_floor = fuel.0;
+        let fuel_ceil = fuel.1;
+        let min_fuel = fuel_floor.min(fuel_ceil);
+
+        Ok(Answer::new("min_fuel", min_fuel)
+            .with("mean_floor", mean_floor)
+            .with("mean_ceil", mean_ceil)
+            .with("fuel_floor", fuel_floor)
+            .with("fuel_ceil", fuel_ceil))
+    }
 }